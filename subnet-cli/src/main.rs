@@ -0,0 +1,560 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+use std::str::FromStr;
+use custom_error::custom_error;
+use subnet::aggregate::aggregate;
+use subnet::cloud::{CloudError, Provider};
+use subnet::dns::{to_bind, to_octodns, ForwardOptions, NameTemplate, TemplateError};
+use subnet::lint::{lint, PlanEntry, Severity};
+use subnet::network::IPNetwork;
+use subnet::report::{Format, Report};
+use subnet::types::{IPAddress, NetmaskError, ParseError, SubnetMask};
+use subnet::vlsm::{HostRequirement, VlsmError};
+
+const USAGE: &str = "\
+Usage:
+  subnet [OPTIONS] <ADDRESS>/<CIDR>          describe the subnet of an address
+  subnet [OPTIONS] <ADDRESS> <NETMASK>       same, with a dot.decimal netmask
+  subnet [OPTIONS] split <NETWORK> <CIDR>    split a network into subnets
+  subnet [OPTIONS] aggregate <NETWORK>...    merge networks into the fewest CIDRs
+  subnet [OPTIONS] vlsm <NETWORK> <REQ>...   plan subnets for host requirements,
+                                             e.g. \"sales: 120 hosts, dmz: 10 hosts\"
+  subnet ptr <NETWORK> <TEMPLATE>            BIND PTR records of every host, named
+                                             e.g. \"host-{b2}-{b3}.example.net\"
+  subnet records <NETWORK> <TEMPLATE> [--skip-gateway] [--exclude <ADDRESS>]...
+                 [--zone <ZONE>]             BIND A records of every host, or an
+                                             octodns JSON zone when a zone is given
+  subnet [OPTIONS] lint [--strict] <FILE>    check an address plan for conflicts;
+                                             FILE holds \"<NAME> <NETWORK>\" lines,
+                                             - reads standard input
+  subnet [OPTIONS] cloud <PROVIDER> <VPC> <ZONES> [TIERS]
+                                             split a VPC across availability zones
+                                             and tiers, e.g. \"aws 10.0.0.0/16
+                                             a,b,c public,private,db\"; PROVIDER is
+                                             aws, azure or gcp
+
+Options:
+  -f, --format <FORMAT>   output format: json, yaml, csv, tsv or table.
+                          Field names are stable within a schema version.
+                          Not taken by ptr and records, which print zone files.
+
+Exit codes:
+  0   success
+  1   invalid usage
+  2   unable to read input
+  10  malformed value             11  CIDR value too large (parsing)
+  12  empty component             13  missing component
+  14  value out of range          15  leading zero
+  16  trailing input              20  CIDR value too large
+  21  retired, no longer used     22  CIDR shorter than parent
+  23  invalid subnet count        24  no networks given
+  25  non contiguous netmask      30  VLSM requirement does not fit
+  40  unknown template placeholder
+  41  unclosed template placeholder
+  42  name outside of the zone
+  50  address plan has errors (or warnings, with --strict)
+  60  prefix length not allowed by the cloud provider
+  61  no availability zones       62  no tiers
+  63  unknown cloud provider
+";
+
+custom_error!{
+    /// Describes an error of the command line calculator
+    CliError
+        Usage{message: String} = "{message}",
+        Parse{source: ParseError} = "{source}",
+        Netmask{source: NetmaskError} = "{source}",
+        Vlsm{source: VlsmError} = "{source}",
+        Template{source: TemplateError} = "{source}",
+        Io{source: io::Error} = "{source}",
+        Cloud{source: CloudError} = "{source}",
+        Plan{line: usize, source: ParseError} = "line {line}: {source}",
+        Lint{output: String, count: usize} = "address plan check failed with {count} finding(s)"
+}
+
+impl CliError {
+    /// Exit code of the error, distinct for every error variant so that
+    /// scripts can branch on it
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage { .. } => 1,
+            CliError::Parse { source } | CliError::Plan { source, .. } => match source {
+                ParseError::GenericError { .. } => 10,
+                ParseError::MaxCidrExceeded { .. } => 11,
+                ParseError::EmptyComponent { .. } => 12,
+                ParseError::MissingComponent { .. } => 13,
+                ParseError::OutOfRange { .. } => 14,
+                ParseError::LeadingZero { .. } => 15,
+                ParseError::TrailingGarbage { .. } => 16,
+            },
+            CliError::Netmask { source } => netmask_exit_code(source),
+            CliError::Vlsm { source } => match source {
+                VlsmError::DoesNotFit { .. } => 30,
+            },
+            CliError::Template { source } => match source {
+                TemplateError::UnknownPlaceholder { .. } => 40,
+                TemplateError::UnclosedPlaceholder { .. } => 41,
+                TemplateError::OutsideZone { .. } => 42,
+            },
+            CliError::Cloud { source } => match source {
+                CloudError::PrefixNotAllowed { .. } => 60,
+                CloudError::NoZones => 61,
+                CloudError::NoTiers => 62,
+                CloudError::UnknownProvider { .. } => 63,
+                CloudError::Netmask { source } => netmask_exit_code(source),
+            },
+            CliError::Io { .. } => 2,
+            CliError::Lint { .. } => 50,
+        }
+    }
+}
+
+/// Exit code of a netmask error, wherever it comes from
+/// 
+/// Parameters:
+/// * `error`: netmask error
+fn netmask_exit_code(error: &NetmaskError) -> i32 {
+    match error {
+        NetmaskError::MaxCidrExceeded { .. } => 20,
+        NetmaskError::ShorterThanParent { .. } => 22,
+        NetmaskError::InvalidSubnetCount { .. } => 23,
+        NetmaskError::NoNetworks => 24,
+        NetmaskError::NonContiguous { .. } => 25,
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(output) => print!("{}", output),
+        Err(error) => {
+            // Failed checks still print their findings
+            if let CliError::Lint { output, .. } = &error {
+                print!("{}", output);
+            }
+
+            eprintln!("subnet: {}", error);
+            if let CliError::Usage { .. } = error {
+                eprint!("\n{}", USAGE);
+            }
+
+            process::exit(error.exit_code());
+        }
+    }
+}
+
+/// Runs the calculator on command line arguments, returning its output
+/// 
+/// Parameters:
+/// * `args`: command line arguments, program name excluded
+fn run(args: &[String]) -> Result<String, CliError> {
+    let (format, args) = parse_format(args)?;
+
+    match args.as_slice() {
+        [] => Err(CliError::Usage { message: "missing network".to_string() }),
+        [flag] if flag == "-h" || flag == "--help" => Ok(USAGE.to_string()),
+        [command, network, cidr] if command == "split" => {
+            let cidr = u8::from_str(cidr).map_err(|_| CliError::Usage { message: format!("invalid CIDR value: {}", cidr) })?;
+            let networks = IPNetwork::from_str(network)?.split(cidr)?;
+            Ok(Report::networks("split", &networks).render(format.unwrap_or(Format::Table)))
+        }
+        [command, ..] if command == "split" => Err(CliError::Usage { message: "split takes a network and a CIDR value".to_string() }),
+        [command, networks @ ..] if command == "aggregate" => {
+            let mut parsed = Vec::new();
+            for network in networks {
+                parsed.push(IPNetwork::from_str(network)?);
+            }
+
+            if parsed.is_empty() {
+                return Err(CliError::Usage { message: "missing networks".to_string() });
+            }
+
+            Ok(Report::networks("aggregate", &aggregate(&parsed)).render(format.unwrap_or(Format::Table)))
+        }
+        [command, ..] if (command == "ptr" || command == "records") && format.is_some() => {
+            Err(CliError::Usage { message: format!("{} prints zone file records and does not take --format", command) })
+        }
+        [command, network, template] if command == "ptr" => {
+            let network = IPNetwork::from_str(network)?;
+            let mut output = String::new();
+            if let Some(delegation) = network.classless_delegation() {
+                let _ = writeln!(output, "; RFC 2317 zone, delegated from {}", delegation.parent_zone);
+            }
+
+            output.push_str(&network.ptr_records(&NameTemplate::from_str(template)?));
+            Ok(output)
+        }
+        [command, ..] if command == "ptr" => Err(CliError::Usage { message: "ptr takes a network and a name template".to_string() }),
+        [command, network, template, options @ ..] if command == "records" => records(&IPNetwork::from_str(network)?, &NameTemplate::from_str(template)?, options),
+        [command, ..] if command == "records" => Err(CliError::Usage { message: "records takes a network and a name template".to_string() }),
+        [command, strict, file] if command == "lint" && strict == "--strict" => check_plan(file, true, format),
+        [command, file] if command == "lint" => check_plan(file, false, format),
+        [command, ..] if command == "lint" => Err(CliError::Usage { message: "lint takes an address plan file".to_string() }),
+        [command, provider, vpc, zones] if command == "cloud" => cloud(provider, vpc, zones, "public,private,db", format),
+        [command, provider, vpc, zones, tiers] if command == "cloud" => cloud(provider, vpc, zones, tiers, format),
+        [command, ..] if command == "cloud" => Err(CliError::Usage { message: "cloud takes a provider, a VPC network, zones and optionally tiers".to_string() }),
+        [command, network, requirements @ ..] if command == "vlsm" => vlsm(&IPNetwork::from_str(network)?, requirements, format),
+        [command, ..] if command == "vlsm" => Err(CliError::Usage { message: "vlsm takes a network and host requirements".to_string() }),
+        [network] => Ok(summary(&IPNetwork::from_str(network)?, format)),
+        [address, netmask] => {
+            let address = IPAddress::from_str(address)?;
+            let cidr = SubnetMask::from_str(netmask)?.to_cidr()?;
+            Ok(summary(&IPNetwork::with_cidr(address, cidr)?, format))
+        }
+        _ => Err(CliError::Usage { message: "too many arguments".to_string() }),
+    }
+}
+
+/// Extracts the output format option from command line arguments, returning
+/// it along with the remaining arguments
+/// 
+/// Parameters:
+/// * `args`: command line arguments, program name excluded
+fn parse_format(args: &[String]) -> Result<(Option<Format>, Vec<String>), CliError> {
+    let mut format = None;
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let value = if arg == "-f" || arg == "--format" {
+            match args.next() {
+                Some(value) => value.as_str(),
+                None => return Err(CliError::Usage { message: "missing output format".to_string() }),
+            }
+        } else if let Some(value) = arg.strip_prefix("--format=") {
+            value
+        } else {
+            rest.push(arg.clone());
+            continue;
+        };
+
+        format = Some(Format::from_str(value).map_err(|_| CliError::Usage { message: format!("unknown output format: {}", value) })?);
+    }
+
+    Ok((format, rest))
+}
+
+/// Describes the subnet of a network, ipcalc style unless another output
+/// format is requested
+/// 
+/// Parameters:
+/// * `network`: network to describe
+/// * `format`: output format
+fn summary(network: &IPNetwork, format: Option<Format>) -> String {
+    let summary = network.summary();
+    if let Some(format) = format {
+        return Report::summary(&summary).render(format);
+    }
+
+    let broadcast = match summary.broadcast {
+        Some(broadcast) => broadcast.to_string(),
+        None => "none".to_string(),
+    };
+
+    let rows = [
+        ("Address:", summary.address.to_string(), Some(summary.address)),
+        ("Netmask:", format!("{} = {}", summary.netmask, network.prefix), Some(IPAddress::from_u32(summary.netmask.to_u32()))),
+        ("Wildcard:", summary.wildcard.to_string(), Some(IPAddress::from_u32(summary.wildcard.to_u32()))),
+        ("Network:", summary.network.to_string(), Some(summary.network.address)),
+        ("Broadcast:", broadcast, summary.broadcast),
+        ("HostMin:", summary.first_host.to_string(), None),
+        ("HostMax:", summary.last_host.to_string(), None),
+        ("Hosts/Net:", summary.usable_hosts.to_string(), None),
+        ("Class:", summary.address.class().to_string(), None),
+        ("Classful:", network.classful().to_string(), None),
+        ("Type:", summary.address.address_type(), None),
+    ];
+
+    let mut output = String::new();
+    for (label, value, bits) in rows {
+        let bits = bits.map(|b| b.to_binary_string()).unwrap_or_default();
+        let line = format!("{:<11}{:<22}{}", label, value, bits);
+        let _ = writeln!(output, "{}", line.trim_end());
+    }
+
+    output
+}
+
+/// Plans subnets for host requirements and renders them, as a table unless
+/// another output format is requested
+/// 
+/// Parameters:
+/// * `network`: parent network
+/// * `requirements`: requirements, each argument may hold several comma separated ones
+/// * `format`: output format
+fn vlsm(network: &IPNetwork, requirements: &[String], format: Option<Format>) -> Result<String, CliError> {
+    let mut parsed = Vec::new();
+    for requirement in requirements.iter().flat_map(|r| r.split(',')) {
+        parsed.push(HostRequirement::from_str(requirement.trim())?);
+    }
+
+    if parsed.is_empty() {
+        return Err(CliError::Usage { message: "missing host requirements".to_string() });
+    }
+
+    Ok(Report::vlsm(&network.vlsm(&parsed)?).render(format.unwrap_or(Format::Table)))
+}
+
+/// Names every host of a network, as BIND A records or as an octodns zone
+/// 
+/// Parameters:
+/// * `network`: network to name
+/// * `template`: template of the host names
+/// * `args`: `--skip-gateway`, `--exclude <ADDRESS>` and `--zone <ZONE>` options
+fn records(network: &IPNetwork, template: &NameTemplate, args: &[String]) -> Result<String, CliError> {
+    let mut options = ForwardOptions::default();
+    let mut zone = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--skip-gateway" => options.skip_gateway = true,
+            "--exclude" | "--zone" => {
+                let value = args.next().ok_or_else(|| CliError::Usage { message: format!("missing value of {}", arg) })?;
+                if arg == "--zone" {
+                    zone = Some(value.as_str());
+                } else {
+                    options.exclude.insert(&IPNetwork::from(IPAddress::from_str(value)?));
+                }
+            }
+            _ => return Err(CliError::Usage { message: format!("unknown option: {}", arg) }),
+        }
+    }
+
+    let records: Vec<_> = network.a_records(template, &options).collect();
+    match zone {
+        Some(zone) => Ok(to_octodns(&records, zone)?),
+        None => Ok(to_bind(&records)),
+    }
+}
+
+/// Splits a VPC network across availability zones and tiers
+/// 
+/// Parameters:
+/// * `provider`: name of the cloud provider
+/// * `vpc`: VPC network
+/// * `zones`: comma separated availability zones
+/// * `tiers`: comma separated tiers
+/// * `format`: output format
+fn cloud(provider: &str, vpc: &str, zones: &str, tiers: &str, format: Option<Format>) -> Result<String, CliError> {
+    let provider = Provider::from_str(provider)?;
+    let zones: Vec<&str> = zones.split(',').map(str::trim).filter(|zone| !zone.is_empty()).collect();
+    let tiers: Vec<&str> = tiers.split(',').map(str::trim).filter(|tier| !tier.is_empty()).collect();
+    let plan = IPNetwork::from_str(vpc)?.cloud_plan(provider, &zones, &tiers)?;
+    Ok(Report::cloud(&plan).render(format.unwrap_or(Format::Table)))
+}
+
+/// Checks an address plan file, failing when it holds errors, or warnings
+/// in strict mode
+/// 
+/// Parameters:
+/// * `file`: path of the plan, `-` for standard input
+/// * `strict`: fail on warnings as well
+/// * `format`: output format, one diagnostic per line by default
+fn check_plan(file: &str, strict: bool, format: Option<Format>) -> Result<String, CliError> {
+    let mut content = String::new();
+    if file == "-" {
+        io::stdin().read_to_string(&mut content)?;
+    } else {
+        content = fs::read_to_string(file)?;
+    }
+
+    let mut entries = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (name, network) = match line.rsplit_once(char::is_whitespace) {
+            Some((name, network)) => (name.trim(), network),
+            None => return Err(CliError::Usage { message: format!("line {}: expected \"<NAME> <NETWORK>\"", i + 1) }),
+        };
+
+        let network = IPNetwork::from_str(network).map_err(|source| CliError::Plan { line: i + 1, source })?;
+        entries.push(PlanEntry::new(name, network));
+    }
+
+    let diagnostics = lint(&entries);
+    let output = match format {
+        Some(format) => Report::diagnostics(&diagnostics).render(format),
+        None => diagnostics.iter().map(|d| format!("{}\n", d)).collect(),
+    };
+
+    let threshold = if strict { Severity::Warning } else { Severity::Error };
+    let count = diagnostics.iter().filter(|d| d.severity >= threshold).count();
+    if count > 0 {
+        return Err(CliError::Lint { output, count });
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn summary_output() {
+        let output = run(&args("192.168.1.2/24"));
+        // Assert that it did not fail
+        assert!(output.is_ok());
+
+        let output = output.unwrap();
+        assert!(output.contains("Address:   192.168.1.2           11000000.10101000.00000001.00000010\n"));
+        assert!(output.contains("Netmask:   255.255.255.0 = 24    11111111.11111111.11111111.00000000\n"));
+        assert!(output.contains("Network:   192.168.1.0/24"));
+        assert!(output.contains("Broadcast: 192.168.1.255"));
+        assert!(output.contains("HostMin:   192.168.1.1\n"));
+        assert!(output.contains("HostMax:   192.168.1.254\n"));
+        assert!(output.contains("Hosts/Net: 254\n"));
+        assert!(output.contains("Class:     C\n"));
+        assert!(output.contains("Classful:  classful (/24)\n"));
+        assert!(output.contains("Type:      private\n"));
+
+        // Address and netmask pair gives the same result
+        assert_eq!(output, run(&args("192.168.1.2 255.255.255.0")).unwrap());
+        assert!(run(&args("8.8.8.8/31")).unwrap().contains("Broadcast: none\n"));
+        assert!(run(&args("8.8.8.8/31")).unwrap().contains("Type:      public\n"));
+        assert!(run(&args("8.8.8.8/31")).unwrap().contains("Classful:  subnetted (/8 + 23 bits)\n"));
+        assert!(run(&args("224.0.0.1/4")).unwrap().contains("Classful:  none\n"));
+        assert!(run(&args("100.64.1.1/10")).unwrap().contains("Type:      Shared Address Space\n"));
+        assert!(run(&args("172.16.0.1/12")).unwrap().contains("Classful:  supernetted (/16 - 4 bits)\n"));
+    }
+
+    #[test]
+    fn vlsm_output() {
+        let output = run(&[
+            "vlsm".to_string(),
+            "192.168.10.0/24".to_string(),
+            "sales: 120 hosts, dmz: 10 hosts".to_string(),
+            "p2p: 2".to_string(),
+        ]).unwrap();
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!("name   hosts  network            netmask          broadcast       spare_hosts", lines[0]);
+        assert_eq!("sales  120    192.168.10.0/25    255.255.255.128  192.168.10.127  6", lines[1]);
+        assert_eq!("p2p    2      192.168.10.144/31  255.255.255.254  -               0", lines[3]);
+    }
+
+    #[test]
+    fn output_formats() {
+        let output = run(&args("--format json 192.168.1.2/24")).unwrap();
+        assert!(output.starts_with("{\"version\":2,\"kind\":\"summary\",\"records\":[{\"address\":\"192.168.1.2\","));
+        assert_eq!(output, run(&args("192.168.1.2 255.255.255.0 -f json")).unwrap());
+        assert!(output.ends_with(",\"class\":\"C\",\"classful\":\"classful (/24)\",\"type\":\"private\"}]}\n"));
+        assert!(run(&args("-f csv 100.64.1.1/10")).unwrap().ends_with(",A,subnetted (/8 + 2 bits),Shared Address Space\n"));
+
+        let output = run(&args("split 10.0.0.0/24 26 --format=csv")).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(5, lines.len());
+        assert_eq!("network,netmask,broadcast,first_host,last_host,total_hosts,usable_hosts", lines[0]);
+        assert_eq!("10.0.0.192/26,255.255.255.192,10.0.0.255,10.0.0.193,10.0.0.254,64,62", lines[4]);
+
+        let output = run(&args("-f tsv aggregate 10.0.0.0/25 10.0.0.128/25 10.0.1.0/24")).unwrap();
+        assert_eq!("network\tnetmask\tbroadcast\tfirst_host\tlast_host\ttotal_hosts\tusable_hosts\n\
+            10.0.0.0/23\t255.255.254.0\t10.0.1.255\t10.0.0.1\t10.0.1.254\t512\t510\n", output);
+
+        assert!(run(&args("-f yaml vlsm 10.0.0.0/24 a:10")).unwrap().starts_with("version: 2\nkind: vlsm\nrecords:\n  - name: \"a\"\n"));
+        assert_eq!(Some(1), run(&args("-f xml 10.0.0.0/8")).err().map(|e| e.exit_code()));
+        assert_eq!(Some(1), run(&args("10.0.0.0/8 -f")).err().map(|e| e.exit_code()));
+    }
+
+    #[test]
+    fn ptr_output() {
+        let output = run(&args("ptr 192.168.1.64/30 host-{b2}-{b3}.example.net")).unwrap();
+        assert_eq!("; RFC 2317 zone, delegated from 1.168.192.in-addr.arpa\n\
+            $ORIGIN 64/30.1.168.192.in-addr.arpa.\n\
+            65\tIN\tPTR\thost-1-65.example.net.\n\
+            66\tIN\tPTR\thost-1-66.example.net.\n", output);
+    }
+
+    #[test]
+    fn records_output() {
+        let output = run(&args("records 10.0.0.0/29 h{b3}.example.net --skip-gateway --exclude 10.0.0.3")).unwrap();
+        assert_eq!("h2.example.net.\tIN\tA\t10.0.0.2\nh4.example.net.\tIN\tA\t10.0.0.4\n\
+            h5.example.net.\tIN\tA\t10.0.0.5\nh6.example.net.\tIN\tA\t10.0.0.6\n", output);
+
+        let output = run(&args("records 10.0.0.0/30 h{b3}.example.net --zone example.net")).unwrap();
+        assert_eq!("{\"h1\":{\"type\":\"A\",\"value\":\"10.0.0.1\"},\"h2\":{\"type\":\"A\",\"value\":\"10.0.0.2\"}}\n", output);
+    }
+
+    #[test]
+    fn lint_output() {
+        let path = std::env::temp_dir().join(format!("subnet-plan-{}.txt", process::id()));
+        fs::write(&path, "# inventory\nvpc prod 10.0.0.0/16\nvlan-10 10.0.1.0/24  # office\n\ngw 10.0.1.1/25\n").unwrap();
+        let file = path.to_str().unwrap();
+
+        match run(&args(&format!("lint {}", file))) {
+            Err(CliError::Lint { output, count }) => {
+                assert_eq!(1, count);
+                assert_eq!("error[cidr-mismatch]: 'gw' (10.0.1.1/25) has prefix /25, but 'vlan-10' (10.0.1.0/24) holding it has prefix /24\n\
+                    warning[overlap]: 'vlan-10' (10.0.1.0/24) is nested in 'vpc prod' (10.0.0.0/16)\n", output);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        fs::write(&path, "vpc 10.0.0.0/16\nvlan-10 10.0.1.0/24\n").unwrap();
+        let output = run(&args(&format!("lint {} --format csv", file))).unwrap();
+        assert_eq!("severity,code,entries,message\nwarning,overlap,\"vpc,vlan-10\",'vlan-10' (10.0.1.0/24) is nested in 'vpc' (10.0.0.0/16)\n", output);
+        assert_eq!(Some(50), run(&args(&format!("lint --strict {}", file))).err().map(|e| e.exit_code()));
+
+        fs::write(&path, "vpc 10.0.0.0/16\nbroken 10.0.1.0/33\n").unwrap();
+        let error = run(&args(&format!("lint {}", file))).unwrap_err();
+        assert_eq!((11, "line 2: ".to_string()), (error.exit_code(), error.to_string()[..8].to_string()));
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Some(2), run(&args(&format!("lint {}", file))).err().map(|e| e.exit_code()));
+    }
+
+    #[test]
+    fn cloud_output() {
+        let output = run(&args("cloud aws 10.0.0.0/16 us-east-1a,us-east-1b")).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(7, lines.len());
+        assert_eq!("zone        tier     network        netmask        first_usable  last_usable   reserved  usable_hosts", lines[0]);
+        assert_eq!("us-east-1a  public   10.0.0.0/19    255.255.224.0  10.0.0.4      10.0.31.254   5         8187", lines[1]);
+        assert_eq!("us-east-1b  db       10.0.160.0/19  255.255.224.0  10.0.160.4    10.0.191.254  5         8187", lines[6]);
+
+        let output = run(&args("cloud gcp 10.1.0.0/24 a app --format csv")).unwrap();
+        assert_eq!("zone,tier,network,netmask,first_usable,last_usable,reserved,usable_hosts\na,app,10.1.0.0/24,255.255.255.0,10.1.0.2,10.1.0.253,4,252\n", output);
+    }
+
+    #[test]
+    fn exit_codes() {
+        let code = |line: &str| run(&args(line)).err().map(|e| e.exit_code());
+
+        assert_eq!(Some(1), run(&[]).err().map(|e| e.exit_code()));
+        assert_eq!(Some(10), code("192.168.x.2/24"));
+        assert_eq!(Some(11), code("192.168.1.2/33"));
+        assert_eq!(Some(13), code("192.168.1.2"));
+        assert_eq!(Some(15), code("192.168.01.2/24"));
+        assert_eq!(Some(16), code("192.168.1.2/24/1"));
+        assert_eq!(Some(25), code("192.168.1.2 255.0.255.0"));
+        assert_eq!(Some(30), code("vlsm 10.0.0.0/28 a:100"));
+        assert_eq!(Some(1), code("vlsm 10.0.0.0/28"));
+        assert_eq!(Some(1), code("vlsm"));
+        assert_eq!(Some(22), code("split 10.0.0.0/24 16"));
+        assert_eq!(Some(1), code("split 10.0.0.0/24"));
+        assert_eq!(Some(40), code("ptr 10.0.0.0/24 host-{b9}"));
+        assert_eq!(Some(41), code("ptr 10.0.0.0/24 host-{b3"));
+        assert_eq!(Some(42), code("records 10.0.0.0/30 h{b3}.example.net --zone example.org"));
+        assert_eq!(Some(1), code("records 10.0.0.0/30 h{b3}.example.net --exclude"));
+        assert_eq!(Some(1), code("ptr 10.0.0.0/24 host-{b3} --format json"));
+        assert_eq!(Some(1), code("-f csv records 10.0.0.0/30 h{b3}.example.net"));
+        assert_eq!(Some(13), code("records 10.0.0.0/30 h{b3}.example.net --exclude 10.0.0"));
+        assert_eq!(Some(60), code("cloud aws 10.0.0.0/27 a,b,c"));
+        assert_eq!(Some(61), code("cloud gcp 10.0.0.0/16 ,"));
+        assert_eq!(Some(63), code("cloud oci 10.0.0.0/16 a"));
+        assert_eq!(Some(1), code("cloud aws 10.0.0.0/16"));
+        assert_eq!(None, code("--help"));
+    }
+}
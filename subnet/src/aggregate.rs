@@ -0,0 +1,90 @@
+use crate::constants::MAX_CIDR;
+use crate::network::{IPNetwork, PrefixLen};
+use crate::types::NetmaskError;
+
+/// Smallest single supernet covering a list of networks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Supernet {
+    /// Covering supernet
+    pub network: IPNetwork,
+    /// Number of addresses in the supernet not covered by any input network
+    pub extra_addresses: u64,
+}
+
+/// Collapses a list of networks into the minimal set of covering CIDR blocks.
+/// Adjacent siblings are merged and networks covered by a larger one dropped.
+/// 
+/// Parameters:
+/// * `networks`: networks to collapse, host bits are ignored
+pub fn aggregate(networks: &[IPNetwork]) -> Vec<IPNetwork> {
+    merge_ranges(networks.iter().map(|network| network.bounds()).collect())
+        .into_iter()
+        .flat_map(|(start, end)| range_to_cidrs(start, end))
+        .collect()
+}
+
+/// Calculates the smallest supernet covering every given network, along with
+/// how many addresses it covers beyond the input networks
+/// 
+/// Parameters:
+/// * `networks`: networks to cover, host bits are ignored
+pub fn supernet(networks: &[IPNetwork]) -> Result<Supernet, NetmaskError> {
+    let ranges = merge_ranges(networks.iter().map(|network| network.bounds()).collect());
+    let (first, last) = match (ranges.first(), ranges.last()) {
+        (Some(first), Some(last)) => (first.0, last.1),
+        _ => return Err(NetmaskError::NoNetworks),
+    };
+
+    // Common prefix of the lowest and highest covered address
+    let cidr = (first ^ last).leading_zeros() as u8;
+    let network = IPNetwork::from_u32(first, PrefixLen::clamped(cidr)).calculate_subnet();
+
+    let covered: u64 = ranges.iter().map(|(start, end)| (end - start) as u64 + 1).sum();
+    let size = 1_u64 << (MAX_CIDR - cidr);
+
+    Ok(Supernet { network, extra_addresses: size - covered })
+}
+
+/// Sorts inclusive ranges and merges the overlapping or adjacent ones, giving
+/// sorted, disjoint and non adjacent ranges
+/// 
+/// Parameters:
+/// * `ranges`: inclusive numeric bounds, in any order
+pub(crate) fn merge_ranges(mut ranges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start as u64 <= last.1 as u64 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+}
+
+/// Splits an inclusive range of addresses into the minimal list of CIDR blocks
+/// 
+/// Parameters:
+/// * `start`: first address of the range
+/// * `end`: last address of the range
+pub(crate) fn range_to_cidrs(start: u32, end: u32) -> Vec<IPNetwork> {
+    let mut result = Vec::new();
+    let mut cursor = start as u64;
+    let end = end as u64;
+
+    while cursor <= end {
+        // Largest block aligned on cursor that does not go past the end
+        let mut size = if cursor == 0 { 1_u64 << MAX_CIDR } else { 1_u64 << cursor.trailing_zeros().min(MAX_CIDR as u32) };
+        while cursor + size - 1 > end {
+            size >>= 1;
+        }
+
+        let cidr = MAX_CIDR - size.trailing_zeros() as u8;
+        result.push(IPNetwork::from_u32(cursor as u32, PrefixLen::clamped(cidr)));
+        cursor += size;
+    }
+
+    result
+}
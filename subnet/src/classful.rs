@@ -0,0 +1,148 @@
+use std::fmt;
+use crate::network::{IPNetwork, PrefixLen};
+use crate::types::{IPAddress, SubnetMask};
+
+/// Historical class of an IPv4 address (RFC 791), from its leading bits
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AddressClass {
+    /// Leading bit 0, 0.0.0.0 to 127.255.255.255
+    A,
+    /// Leading bits 10, 128.0.0.0 to 191.255.255.255
+    B,
+    /// Leading bits 110, 192.0.0.0 to 223.255.255.255
+    C,
+    /// Leading bits 1110, multicast, 224.0.0.0 to 239.255.255.255
+    D,
+    /// Leading bits 1111, reserved, 240.0.0.0 to 255.255.255.255
+    E,
+}
+
+/// How the prefix length of a network relates to the default prefix length
+/// of its class
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClassfulRelation {
+    /// The prefix length is the default one of the class
+    Classful,
+    /// The prefix length is longer than the default, borrowing host bits
+    Subnetted {
+        /// Number of host bits borrowed for subnetting
+        borrowed_bits: u8,
+    },
+    /// The prefix length is shorter than the default, grouping several
+    /// classful networks
+    Supernetted {
+        /// Number of network bits given up
+        bits: u8,
+    },
+}
+
+/// Classful analysis of a network
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClassfulAnalysis {
+    /// Class of the network address
+    pub class: AddressClass,
+    /// Default prefix length of the class, none for classes D and E
+    pub default_prefix: Option<PrefixLen>,
+    /// Default subnet mask of the class, none for classes D and E
+    pub default_mask: Option<SubnetMask>,
+    /// Relation of the prefix length to the default one, none for classes D and E
+    pub relation: Option<ClassfulRelation>,
+}
+
+impl AddressClass {
+    /// Returns the default prefix length of this class: /8, /16 or /24 for
+    /// classes A, B and C, none for classes D and E
+    pub fn default_prefix(&self) -> Option<PrefixLen> {
+        match self {
+            AddressClass::A => Some(PrefixLen::clamped(8)),
+            AddressClass::B => Some(PrefixLen::clamped(16)),
+            AddressClass::C => Some(PrefixLen::clamped(24)),
+            AddressClass::D | AddressClass::E => None,
+        }
+    }
+
+    /// Returns the default subnet mask of this class, none for classes D and E
+    pub fn default_mask(&self) -> Option<SubnetMask> {
+        self.default_prefix().map(|prefix| prefix.netmask())
+    }
+}
+
+impl fmt::Display for AddressClass {
+    /// Formats a class as its letter
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let letter = match self {
+            AddressClass::A => "A",
+            AddressClass::B => "B",
+            AddressClass::C => "C",
+            AddressClass::D => "D",
+            AddressClass::E => "E",
+        };
+
+        write!(f, "{}", letter)
+    }
+}
+
+impl ClassfulAnalysis {
+    /// Returns the number of host bits borrowed for subnetting, 0 unless the
+    /// network is subnetted
+    pub fn borrowed_bits(&self) -> u8 {
+        match self.relation {
+            Some(ClassfulRelation::Subnetted { borrowed_bits }) => borrowed_bits,
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for ClassfulAnalysis {
+    /// Formats the relation to the default prefix length, e.g.
+    /// `subnetted (/8 + 23 bits)`, or `none` for classes D and E
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.default_prefix, self.relation) {
+            (Some(default), Some(ClassfulRelation::Classful)) => write!(f, "classful (/{})", default),
+            (Some(default), Some(ClassfulRelation::Subnetted { borrowed_bits })) => write!(f, "subnetted (/{} + {} bits)", default, borrowed_bits),
+            (Some(default), Some(ClassfulRelation::Supernetted { bits })) => write!(f, "supernetted (/{} - {} bits)", default, bits),
+            _ => write!(f, "none"),
+        }
+    }
+}
+
+impl IPAddress {
+    /// Returns the historical class of this address
+    pub fn class(&self) -> AddressClass {
+        match self.b0.leading_ones() {
+            0 => AddressClass::A,
+            1 => AddressClass::B,
+            2 => AddressClass::C,
+            3 => AddressClass::D,
+            _ => AddressClass::E,
+        }
+    }
+
+    /// Returns the default classful subnet mask of this address, none for
+    /// classes D and E
+    pub fn classful_mask(&self) -> Option<SubnetMask> {
+        self.class().default_mask()
+    }
+}
+
+impl IPNetwork {
+    /// Analyzes this network in classful terms: class, default mask, and
+    /// whether the prefix length subnets or supernets the class
+    pub fn classful(&self) -> ClassfulAnalysis {
+        let class = self.address.class();
+        let default_prefix = class.default_prefix();
+
+        let relation = default_prefix.map(|default| {
+            let (cidr, default) = (self.cidr(), default.get());
+            if cidr > default {
+                ClassfulRelation::Subnetted { borrowed_bits: cidr - default }
+            } else if cidr < default {
+                ClassfulRelation::Supernetted { bits: default - cidr }
+            } else {
+                ClassfulRelation::Classful
+            }
+        });
+
+        ClassfulAnalysis { class, default_prefix, default_mask: class.default_mask(), relation }
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+use std::str::FromStr;
+use custom_error::custom_error;
+use crate::network::IPNetwork;
+use crate::types::{IPAddress, NetmaskError};
+
+custom_error!{
+    /// Describes an error while planning cloud subnets
+    pub CloudError
+        PrefixNotAllowed{provider: Provider, cidr: u8, min: u8, max: u8} = "{provider} does not allow /{cidr} networks, only /{min} to /{max}",
+        NoZones = "At least one availability zone is required",
+        NoTiers = "At least one tier is required",
+        UnknownProvider{name: String} = "Unknown cloud provider '{name}', expected aws, azure or gcp",
+        Netmask{source: NetmaskError} = "{source}"
+}
+
+/// Cloud provider, each with its own reserved addresses and allowed prefixes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Provider {
+    /// Amazon Web Services VPC
+    Aws,
+    /// Microsoft Azure virtual network
+    Azure,
+    /// Google Cloud VPC
+    Gcp,
+}
+
+/// Subnet planned for an availability zone and a tier
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CloudSubnet {
+    /// Availability zone, e.g. `us-east-1a`
+    pub zone: String,
+    /// Tier, e.g. `public`
+    pub tier: String,
+    /// Allocated network
+    pub network: IPNetwork,
+    /// Addresses the provider keeps for itself
+    pub reserved: Vec<IPAddress>,
+    /// First address left to instances
+    pub first_usable: IPAddress,
+    /// Last address left to instances
+    pub last_usable: IPAddress,
+    /// Number of addresses left to instances
+    pub usable_hosts: u64,
+}
+
+impl Provider {
+    /// Returns the shortest and longest prefix length the provider allows
+    /// for a subnet: /16 to /28 on AWS, /2 to /29 on Azure, /8 to /29 on GCP
+    pub fn prefix_range(&self) -> (u8, u8) {
+        match self {
+            Provider::Aws => (16, 28),
+            Provider::Azure => (2, 29),
+            Provider::Gcp => (8, 29),
+        }
+    }
+
+    /// Checks that the provider allows a prefix length
+    /// 
+    /// Parameters:
+    /// * `cidr`: CIDR value to check
+    pub fn check_prefix(&self, cidr: u8) -> Result<(), CloudError> {
+        let (min, max) = self.prefix_range();
+        if cidr < min || cidr > max {
+            return Err(CloudError::PrefixNotAllowed { provider: *self, cidr, min, max });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the addresses of a subnet the provider reserves:
+    /// * AWS: network, VPC router, DNS, future use and broadcast (5)
+    /// * Azure: network, default gateway, two DNS and broadcast (5)
+    /// * GCP: network, default gateway, second-to-last and broadcast (4)
+    /// 
+    /// Parameters:
+    /// * `network`: subnet to look at
+    pub fn reserved(&self, network: &IPNetwork) -> Vec<IPAddress> {
+        let (start, end) = network.bounds();
+        let values: &[u32] = match self {
+            Provider::Aws | Provider::Azure => &[start, start + 1, start + 2, start + 3, end],
+            Provider::Gcp => &[start, start + 1, end - 1, end],
+        };
+
+        values.iter().map(|&value| IPAddress::from_u32(value)).collect()
+    }
+
+    /// Returns the name of the provider, as accepted by `from_str`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Provider::Aws => "aws",
+            Provider::Azure => "azure",
+            Provider::Gcp => "gcp",
+        }
+    }
+}
+
+impl FromStr for Provider {
+    type Err = CloudError;
+
+    /// Constructs a provider from its name: `aws`, `azure` or `gcp`
+    fn from_str(provider: &str) -> Result<Provider, CloudError> {
+        match provider.to_ascii_lowercase().as_str() {
+            "aws" => Ok(Provider::Aws),
+            "azure" => Ok(Provider::Azure),
+            "gcp" => Ok(Provider::Gcp),
+            _ => Err(CloudError::UnknownProvider { name: provider.to_string() }),
+        }
+    }
+}
+
+impl fmt::Display for Provider {
+    /// Formats a provider as its name
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl IPNetwork {
+    /// Splits this VPC network into equal subnets, one per tier and
+    /// availability zone. Subnets of a tier are contiguous, e.g. every public
+    /// subnet comes before the private ones. Both the VPC and the subnet
+    /// prefix lengths must be allowed by the provider.
+    /// 
+    /// Parameters:
+    /// * `provider`: cloud provider
+    /// * `zones`: availability zones, e.g. `us-east-1a`
+    /// * `tiers`: tiers, e.g. `public`, `private` and `db`
+    pub fn cloud_plan(&self, provider: Provider, zones: &[&str], tiers: &[&str]) -> Result<Vec<CloudSubnet>, CloudError> {
+        if zones.is_empty() {
+            return Err(CloudError::NoZones);
+        }
+
+        if tiers.is_empty() {
+            return Err(CloudError::NoTiers);
+        }
+
+        provider.check_prefix(self.cidr())?;
+        let count = (zones.len() * tiers.len()) as u32;
+        let cidr = self.split_cidr(count)?;
+        provider.check_prefix(cidr)?;
+
+        let pairs = tiers.iter().flat_map(|tier| zones.iter().map(move |zone| (tier, zone)));
+        let subnets = self.subnets(cidr)?.zip(pairs).map(|(network, (tier, zone))| {
+            let reserved = provider.reserved(&network);
+            let (start, end) = network.bounds();
+            let (first, last) = match provider {
+                Provider::Aws | Provider::Azure => (start + 4, end - 1),
+                Provider::Gcp => (start + 2, end - 2),
+            };
+
+            CloudSubnet {
+                zone: zone.to_string(),
+                tier: tier.to_string(),
+                network,
+                usable_hosts: u64::from(end - start + 1) - reserved.len() as u64,
+                reserved,
+                first_usable: IPAddress::from_u32(first),
+                last_usable: IPAddress::from_u32(last),
+            }
+        }).collect();
+
+        Ok(subnets)
+    }
+}
@@ -1,14 +1,12 @@
-/// Standard CIDR values:
-/// * `UNDEF_CIDR`: undefined CIDR
-/// * `MAX_CIDR`: maximum allowed CIDR value
+/// Former marker of an IP address without CIDR value. Addresses no longer
+/// carry a CIDR value: use `IPAddress` for a bare address and `IPNetwork`,
+/// whose `PrefixLen` is always valid, for an address with a prefix.
+#[deprecated(note = "use `IPAddress` for bare addresses and `IPNetwork` for addresses with a prefix")]
 pub const UNDEF_CIDR: u8 = 0xFA;
-/// Standard CIDR values:
-/// * `UNDEF_CIDR`: undefined CIDR
-/// * `MAX_CIDR`: maximum allowed CIDR value
-/// Standard CIDR values:
-/// * `UNDEF_CIDR`: undefined CIDR
-/// * `MAX_CIDR`: maximum allowed CIDR value
+/// Maximum allowed CIDR value
 pub const MAX_CIDR: u8 = 0x20;
+/// Maximum allowed CIDR value for an IPv6 address
+pub const MAX_CIDR_V6: u8 = 0x80;
 
 /// Minimum block value (hex)
 /// This is the minimum value of an IP octet
@@ -16,3 +14,6 @@ pub const MIN_BLOCK: u8 = 0x00;
 /// Maximum block value (hex)
 /// This is the maximum value of an IP octed
 pub const MAX_BLOCK: u8 = 0xFF;
+
+/// Number of 16 bit segments in an IPv6 address
+pub const V6_SEGMENTS: usize = 8;
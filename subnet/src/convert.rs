@@ -0,0 +1,137 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use crate::network::{IPNetwork, IPv6Network, IPv6PrefixLen, PrefixLen};
+use crate::types::{IPAddress, IPv6Address, ParseError, SubnetMask};
+
+impl From<u32> for IPAddress {
+    fn from(value: u32) -> IPAddress {
+        IPAddress::from_u32(value)
+    }
+}
+
+impl From<[u8; 4]> for IPAddress {
+    fn from([b0, b1, b2, b3]: [u8; 4]) -> IPAddress {
+        IPAddress::new(b0, b1, b2, b3)
+    }
+}
+
+impl From<Ipv4Addr> for IPAddress {
+    fn from(address: Ipv4Addr) -> IPAddress {
+        IPAddress::from(address.octets())
+    }
+}
+
+impl TryFrom<IpAddr> for IPAddress {
+    type Error = ParseError;
+
+    /// Fails on IPv6 addresses, which have no IPAddress equivalent
+    fn try_from(address: IpAddr) -> Result<IPAddress, ParseError> {
+        match address {
+            IpAddr::V4(address) => Ok(IPAddress::from(address)),
+            IpAddr::V6(address) => Err(ParseError::GenericError { position: "IPv4 address".to_string(), value: address.to_string(), offset: 0 }),
+        }
+    }
+}
+
+impl From<IPAddress> for u32 {
+    fn from(address: IPAddress) -> u32 {
+        address.to_u32()
+    }
+}
+
+impl From<IPAddress> for [u8; 4] {
+    fn from(address: IPAddress) -> [u8; 4] {
+        [address.b0, address.b1, address.b2, address.b3]
+    }
+}
+
+impl From<IPAddress> for Ipv4Addr {
+    fn from(address: IPAddress) -> Ipv4Addr {
+        Ipv4Addr::new(address.b0, address.b1, address.b2, address.b3)
+    }
+}
+
+impl From<IPAddress> for IpAddr {
+    fn from(address: IPAddress) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::from(address))
+    }
+}
+
+impl From<IPAddress> for IPNetwork {
+    /// Gives the single host network (/32) of an address
+    fn from(address: IPAddress) -> IPNetwork {
+        IPNetwork::new(address, PrefixLen::MAX)
+    }
+}
+
+impl From<u32> for SubnetMask {
+    fn from(value: u32) -> SubnetMask {
+        SubnetMask::from_u32(value)
+    }
+}
+
+impl From<[u8; 4]> for SubnetMask {
+    fn from([b0, b1, b2, b3]: [u8; 4]) -> SubnetMask {
+        SubnetMask::new(b0, b1, b2, b3)
+    }
+}
+
+impl From<Ipv4Addr> for SubnetMask {
+    fn from(netmask: Ipv4Addr) -> SubnetMask {
+        SubnetMask::from(netmask.octets())
+    }
+}
+
+impl From<SubnetMask> for u32 {
+    fn from(netmask: SubnetMask) -> u32 {
+        netmask.to_u32()
+    }
+}
+
+impl From<SubnetMask> for [u8; 4] {
+    fn from(netmask: SubnetMask) -> [u8; 4] {
+        [netmask.b0, netmask.b1, netmask.b2, netmask.b3]
+    }
+}
+
+impl From<SubnetMask> for Ipv4Addr {
+    fn from(netmask: SubnetMask) -> Ipv4Addr {
+        Ipv4Addr::new(netmask.b0, netmask.b1, netmask.b2, netmask.b3)
+    }
+}
+
+impl From<Ipv6Addr> for IPv6Address {
+    fn from(address: Ipv6Addr) -> IPv6Address {
+        IPv6Address::new(address.segments())
+    }
+}
+
+impl TryFrom<IpAddr> for IPv6Address {
+    type Error = ParseError;
+
+    /// Fails on IPv4 addresses, use `Ipv4Addr::to_ipv6_mapped` first if needed
+    fn try_from(address: IpAddr) -> Result<IPv6Address, ParseError> {
+        match address {
+            IpAddr::V6(address) => Ok(IPv6Address::from(address)),
+            IpAddr::V4(address) => Err(ParseError::GenericError { position: "IPv6 address".to_string(), value: address.to_string(), offset: 0 }),
+        }
+    }
+}
+
+impl From<IPv6Address> for Ipv6Addr {
+    fn from(address: IPv6Address) -> Ipv6Addr {
+        Ipv6Addr::from(address.segments)
+    }
+}
+
+impl From<IPv6Address> for IpAddr {
+    fn from(address: IPv6Address) -> IpAddr {
+        IpAddr::V6(Ipv6Addr::from(address))
+    }
+}
+
+impl From<IPv6Address> for IPv6Network {
+    /// Gives the single host network (/128) of an address
+    fn from(address: IPv6Address) -> IPv6Network {
+        IPv6Network::new(address, IPv6PrefixLen::MAX)
+    }
+}
@@ -0,0 +1,413 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Write};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use custom_error::custom_error;
+use crate::constants::V6_SEGMENTS;
+use crate::network::{IPNetwork, IPv6Network};
+use crate::report::{json_value, Value};
+use crate::set::IpSet;
+use crate::types::{IPAddress, IPv6Address};
+
+/// Suffix of IPv4 reverse DNS names
+const REVERSE_SUFFIX: &str = "in-addr.arpa";
+
+custom_error!{
+    /// Describes an error in a DNS name template
+    pub TemplateError
+        UnknownPlaceholder{name: String, offset: usize} = "Unknown placeholder '{{{name}}}' at offset {offset}",
+        UnclosedPlaceholder{offset: usize} = "Unclosed placeholder at offset {offset}",
+        OutsideZone{name: String, zone: String} = "Name '{name}' is not within zone '{zone}'"
+}
+
+/// Host name template, like `host-{b2}-{b3}.example.net`. Placeholders
+/// `{b0}` to `{b3}` expand to the bytes of the address, `{s0}` to `{s7}` to
+/// its hexadecimal IPv6 segments, `{ip}` to the whole address with dashes,
+/// e.g. `192-168-1-2`. IPv6 addresses expose their last 32 bits as bytes,
+/// IPv4 addresses their IPv4-mapped form as segments. Rendered names are
+/// always fully qualified.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NameTemplate {
+    parts: Vec<Part>,
+}
+
+/// Piece of a name template
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    /// Literal text
+    Text(String),
+    /// Byte of the address, by index
+    Byte(usize),
+    /// IPv6 segment of the address, by index
+    Segment(usize),
+    /// Whole address, with dashes
+    Address,
+}
+
+/// RFC 2317 classless delegation of a network longer than /24
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClasslessDelegation {
+    /// Octet aligned zone the network belongs to, e.g. `1.168.192.in-addr.arpa`
+    pub parent_zone: String,
+    /// Label of the delegated zone within the parent zone, e.g. `64/26`
+    pub label: String,
+    /// Delegated zone, e.g. `64/26.1.168.192.in-addr.arpa`
+    pub zone: String,
+    /// CNAME records of the parent zone, as (owner label, target) pairs,
+    /// e.g. (`65`, `65.64/26.1.168.192.in-addr.arpa.`)
+    pub cnames: Vec<(String, String)>,
+}
+
+impl NameTemplate {
+    /// Creates a new name template, validating its placeholders
+    /// 
+    /// Parameters:
+    /// * `template`: template string, like `host-{b2}-{b3}.example.net`
+    pub fn new(template: &str) -> Result<NameTemplate, TemplateError> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        let mut offset = 0;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => return Err(TemplateError::UnclosedPlaceholder { offset: offset + start }),
+            };
+
+            parts.push(match &rest[start + 1..end] {
+                "b0" => Part::Byte(0),
+                "b1" => Part::Byte(1),
+                "b2" => Part::Byte(2),
+                "b3" => Part::Byte(3),
+                "s0" => Part::Segment(0),
+                "s1" => Part::Segment(1),
+                "s2" => Part::Segment(2),
+                "s3" => Part::Segment(3),
+                "s4" => Part::Segment(4),
+                "s5" => Part::Segment(5),
+                "s6" => Part::Segment(6),
+                "s7" => Part::Segment(7),
+                "ip" => Part::Address,
+                name => return Err(TemplateError::UnknownPlaceholder { name: name.to_string(), offset: offset + start }),
+            });
+
+            offset += end + 1;
+            rest = &rest[end + 1..];
+        }
+
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+
+        Ok(NameTemplate { parts })
+    }
+
+    /// Renders the fully qualified name of an address
+    /// 
+    /// Parameters:
+    /// * `address`: address to name
+    pub fn render(&self, address: &IPAddress) -> String {
+        let segments = Ipv4Addr::from(*address).to_ipv6_mapped().segments();
+        self.expand([address.b0, address.b1, address.b2, address.b3], segments, &address.to_string())
+    }
+
+    /// Renders the fully qualified name of an IPv6 address
+    /// 
+    /// Parameters:
+    /// * `address`: address to name
+    pub fn render_v6(&self, address: &IPv6Address) -> String {
+        let bytes = Ipv6Addr::from(*address).octets();
+        self.expand([bytes[12], bytes[13], bytes[14], bytes[15]], address.segments, &address.to_string())
+    }
+
+    /// Expands the placeholders of this template
+    fn expand(&self, bytes: [u8; 4], segments: [u16; V6_SEGMENTS], address: &str) -> String {
+        let mut name = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => name.push_str(text),
+                Part::Byte(i) => name.push_str(&bytes[*i].to_string()),
+                Part::Segment(i) => name.push_str(&format!("{:x}", segments[*i])),
+                Part::Address => name.push_str(&address.replace(['.', ':'], "-")),
+            }
+        }
+
+        if !name.ends_with('.') {
+            name.push('.');
+        }
+
+        name
+    }
+}
+
+impl FromStr for NameTemplate {
+    type Err = TemplateError;
+
+    /// Constructs a name template from string slice, like `host-{b2}-{b3}.example.net`
+    fn from_str(template: &str) -> Result<NameTemplate, TemplateError> {
+        NameTemplate::new(template)
+    }
+}
+
+impl fmt::Display for NameTemplate {
+    /// Formats a name template back to its string form
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => write!(f, "{}", text)?,
+                Part::Byte(i) => write!(f, "{{b{}}}", i)?,
+                Part::Segment(i) => write!(f, "{{s{}}}", i)?,
+                Part::Address => write!(f, "{{ip}}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl IPAddress {
+    /// Returns the reverse DNS name of this address, e.g. `2.1.168.192.in-addr.arpa`
+    pub fn reverse_name(&self) -> String {
+        format!("{}.{}.{}.{}.{}", self.b3, self.b2, self.b1, self.b0, REVERSE_SUFFIX)
+    }
+}
+
+impl IPNetwork {
+    /// Returns the reverse DNS zones covering this network. Prefixes that are
+    /// not octet aligned span several zones, e.g. a /20 spans sixteen /24
+    /// zones, while prefixes longer than /24 get their RFC 2317 classless
+    /// zone, e.g. `64/26.1.168.192.in-addr.arpa`.
+    pub fn reverse_zones(&self) -> Vec<String> {
+        if let Some(delegation) = self.classless_delegation() {
+            return vec![delegation.zone];
+        }
+
+        // Round the prefix up to the next octet boundary
+        let octets = self.cidr().div_ceil(8) as usize;
+        let zone_prefix = octets as u8 * 8;
+
+        self.subnet_starts(zone_prefix)
+            .map(|start| zone_name(&IPAddress::from_u32(start), octets))
+            .collect()
+    }
+
+    /// Returns the RFC 2317 classless delegation of this network, none unless
+    /// its prefix is longer than /24
+    pub fn classless_delegation(&self) -> Option<ClasslessDelegation> {
+        if self.cidr() <= 24 {
+            return None;
+        }
+
+        let network = self.calculate_subnet();
+        let parent_zone = zone_name(&network.address, 3);
+        let label = format!("{}/{}", network.address.b3, network.cidr());
+        let zone = format!("{}.{}", label, parent_zone);
+        let cnames = network.hosts().map(|host| (host.b3.to_string(), format!("{}.{}.", host.b3, zone))).collect();
+
+        Some(ClasslessDelegation { parent_zone, label, zone, cnames })
+    }
+
+    /// Renders BIND zone file fragments holding a PTR record for every host of
+    /// this network, one `$ORIGIN` block per reverse zone
+    /// 
+    /// Parameters:
+    /// * `template`: template of the host names
+    pub fn ptr_records(&self, template: &NameTemplate) -> String {
+        let mut output = String::new();
+        let mut origin = String::new();
+        let classless = self.classless_delegation().map(|delegation| delegation.zone);
+
+        for host in self.hosts() {
+            let zone = match &classless {
+                Some(zone) => zone.clone(),
+                None => zone_name(&host, self.cidr().div_ceil(8).max(1) as usize),
+            };
+
+            if zone != origin {
+                let _ = writeln!(output, "$ORIGIN {}.", zone);
+                origin = zone;
+            }
+
+            let label = match self.cidr() {
+                0..=8 => format!("{}.{}.{}", host.b3, host.b2, host.b1),
+                9..=16 => format!("{}.{}", host.b3, host.b2),
+                _ => host.b3.to_string(),
+            };
+
+            let _ = writeln!(output, "{}\tIN\tPTR\t{}", label, template.render(&host));
+        }
+
+        output
+    }
+
+    /// Returns the numeric start addresses of the subnets of the given prefix
+    /// length covering this network
+    fn subnet_starts(&self, cidr: u8) -> impl Iterator<Item = u32> {
+        let (first, last) = self.bounds();
+        let step = 1u64 << (32 - u32::from(cidr));
+        (u64::from(first)..=u64::from(last)).step_by(step as usize).map(|start| start as u32)
+    }
+}
+
+impl ClasslessDelegation {
+    /// Renders the records of the parent zone delegating the classless zone:
+    /// NS records for the delegated zone, then a CNAME for every host
+    /// 
+    /// Parameters:
+    /// * `nameservers`: fully qualified name servers of the delegated zone
+    pub fn to_bind(&self, nameservers: &[&str]) -> String {
+        let mut output = format!("$ORIGIN {}.\n", self.parent_zone);
+        for nameserver in nameservers {
+            let dot = if nameserver.ends_with('.') { "" } else { "." };
+            let _ = writeln!(output, "{}\tIN\tNS\t{}{}", self.label, nameserver, dot);
+        }
+
+        for (owner, target) in &self.cnames {
+            let _ = writeln!(output, "{}\tIN\tCNAME\t{}", owner, target);
+        }
+
+        output
+    }
+}
+
+/// Options of forward record generation. Network and broadcast addresses
+/// are never named, since records come from the host iterators.
+#[derive(Clone, Debug, Default)]
+pub struct ForwardOptions {
+    /// Skip the gateway, by convention the first host address
+    pub skip_gateway: bool,
+    /// IPv4 addresses to skip
+    pub exclude: IpSet,
+    /// IPv6 addresses to skip
+    pub exclude_v6: HashSet<IPv6Address>,
+}
+
+/// Address record of a forward zone
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForwardRecord {
+    /// Fully qualified name, e.g. `host-1-2.example.net.`
+    pub name: String,
+    /// Record type, `A` or `AAAA`
+    pub record_type: &'static str,
+    /// Address the name points to
+    pub value: String,
+}
+
+impl IPNetwork {
+    /// Returns an A record for every host of this network, named after a
+    /// template
+    /// 
+    /// Parameters:
+    /// * `template`: template of the host names
+    /// * `options`: addresses to skip
+    pub fn a_records<'a>(&self, template: &'a NameTemplate, options: &'a ForwardOptions) -> impl Iterator<Item = ForwardRecord> + 'a {
+        self.hosts()
+            .skip(options.skip_gateway as usize)
+            .filter(|host| !options.exclude.contains(host))
+            .map(|host| ForwardRecord { name: template.render(&host), record_type: "A", value: host.to_string() })
+    }
+}
+
+impl IPv6Network {
+    /// Returns an AAAA record for every host of this network, named after a
+    /// template. The iterator may be huge, e.g. for a /64.
+    /// 
+    /// Parameters:
+    /// * `template`: template of the host names
+    /// * `options`: addresses to skip
+    pub fn aaaa_records<'a>(&self, template: &'a NameTemplate, options: &'a ForwardOptions) -> impl Iterator<Item = ForwardRecord> + 'a {
+        self.hosts()
+            .skip(options.skip_gateway as usize)
+            .filter(|host| !options.exclude_v6.contains(host))
+            .map(|host| ForwardRecord { name: template.render_v6(&host), record_type: "AAAA", value: host.to_string() })
+    }
+}
+
+/// Renders address records as BIND zone file lines
+/// 
+/// Parameters:
+/// * `records`: records to render
+pub fn to_bind(records: &[ForwardRecord]) -> String {
+    let mut output = String::new();
+    for record in records {
+        let _ = writeln!(output, "{}\tIN\t{}\t{}", record.name, record.record_type, record.value);
+    }
+
+    output
+}
+
+/// Values of the records of a given type sharing a name
+type TypeValues<'a> = (&'a str, Vec<&'a str>);
+
+/// Renders address records as an octodns zone in JSON, which dnscontrol
+/// also reads through its OCTODNS provider. Names are made relative to the
+/// zone and records sharing a name and type are merged into `values`.
+/// 
+/// Parameters:
+/// * `records`: records to render
+/// * `zone`: zone holding the records, e.g. `example.net`
+pub fn to_octodns(records: &[ForwardRecord], zone: &str) -> Result<String, TemplateError> {
+    let zone = zone.trim_end_matches('.');
+    // Labels keep the order of their first record, the index finds them
+    let mut labels: Vec<(String, Vec<TypeValues>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for record in records {
+        let name = record.name.trim_end_matches('.');
+        let label = if name == zone {
+            String::new()
+        } else {
+            match name.strip_suffix(zone).and_then(|n| n.strip_suffix('.')) {
+                Some(label) => label.to_string(),
+                None => return Err(TemplateError::OutsideZone { name: record.name.clone(), zone: zone.to_string() }),
+            }
+        };
+
+        let i = *index.entry(label.clone()).or_insert_with(|| {
+            labels.push((label, Vec::new()));
+            labels.len() - 1
+        });
+
+        let types = &mut labels[i].1;
+        match types.iter_mut().find(|(t, _)| *t == record.record_type) {
+            Some((_, values)) => values.push(&record.value),
+            None => types.push((record.record_type, vec![&record.value])),
+        }
+    }
+
+    let entries: Vec<String> = labels.iter().map(|(label, types)| {
+        let records: Vec<String> = types.iter().map(|(record_type, values)| {
+            let values: Vec<String> = values.iter().map(|v| json_value(&Value::Text(v.to_string()))).collect();
+            match values.as_slice() {
+                [value] => format!("{{\"type\":\"{}\",\"value\":{}}}", record_type, value),
+                _ => format!("{{\"type\":\"{}\",\"values\":[{}]}}", record_type, values.join(",")),
+            }
+        }).collect();
+
+        let records = match records.as_slice() {
+            [record] => record.clone(),
+            _ => format!("[{}]", records.join(",")),
+        };
+
+        format!("{}:{}", json_value(&Value::Text(label.clone())), records)
+    }).collect();
+
+    Ok(format!("{{{}}}\n", entries.join(",")))
+}
+
+/// Returns the reverse zone holding an address, from its leading octets
+/// 
+/// Parameters:
+/// * `address`: address within the zone
+/// * `octets`: number of leading octets naming the zone, from 0 to 3
+fn zone_name(address: &IPAddress, octets: usize) -> String {
+    let bytes = [address.b0, address.b1, address.b2];
+    let mut labels: Vec<String> = bytes[..octets.min(3)].iter().rev().map(|b| b.to_string()).collect();
+    labels.push(REVERSE_SUFFIX.to_string());
+    labels.join(".")
+}
@@ -0,0 +1,159 @@
+use crate::parse::reject_prefix;
+use crate::types::{IPAddress, ParseError};
+
+/// Number notation of a single inet_aton component
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Radix {
+    /// Plain decimal, e.g. `10`
+    Decimal,
+    /// Leading zero octal, e.g. `012`
+    Octal,
+    /// `0x` prefixed hexadecimal, e.g. `0x0a`
+    Hex,
+}
+
+/// Address form detected by the inet_aton parser
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InetAtonForm {
+    /// Number of dot separated components, from 1 (`a`) to 4 (`a.b.c.d`)
+    pub parts: usize,
+    /// Notation of every component, in order
+    pub radixes: Vec<Radix>,
+}
+
+/// IP address parsed by the inet_aton parser, along with its detected form
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InetAton {
+    /// Parsed IP address
+    pub address: IPAddress,
+    /// Form the address was written in
+    pub form: InetAtonForm,
+}
+
+impl InetAtonForm {
+    /// Checks whether this is the standard four part decimal form
+    pub fn is_standard(&self) -> bool {
+        self.parts == 4 && self.radixes.iter().all(|&r| r == Radix::Decimal)
+    }
+}
+
+impl IPAddress {
+    /// Constructs an IP address from any form accepted by BSD/glibc inet_aton:
+    /// `a.b.c.d`, `a.b.c` (c is 16 bit), `a.b` (b is 24 bit) or `a` (32 bit),
+    /// each component being decimal, `0x` hexadecimal or leading zero octal.
+    /// Use [`IPNetwork::from_str_with_options`](crate::network::IPNetwork::from_str_with_options)
+    /// for a network with a CIDR suffix.
+    /// 
+    /// Parameters:
+    /// * `address`: string slice value with IP address
+    pub fn from_inet_aton(address: &str) -> Result<InetAton, ParseError> {
+        reject_prefix(address)?;
+
+        let mut values = Vec::with_capacity(4);
+        let mut radixes = Vec::with_capacity(4);
+        let mut offset = 0;
+
+        for chunk in address.split('.') {
+            if values.len() == 4 {
+                return Err(ParseError::TrailingGarbage { value: address[offset - 1..].to_string(), offset: offset - 1 });
+            }
+
+            let (value, radix) = parse_component(chunk, offset, values.len())?;
+            values.push((value, offset, chunk));
+            radixes.push(radix);
+            offset += chunk.len() + 1;
+        }
+
+        // Every component but the last one is a single byte, the last one
+        // fills all remaining bytes
+        let (last, leading) = match values.split_last() {
+            Some(split) => split,
+            None => return Err(ParseError::EmptyComponent { position: "byte 0".to_string(), offset: 0 }),
+        };
+        let mut result = 0_u32;
+        for (i, &(value, offset, chunk)) in leading.iter().enumerate() {
+            if value > 0xFF {
+                return Err(ParseError::OutOfRange { position: format!("byte {}", i), value: chunk.to_string(), offset });
+            }
+
+            result |= (value as u32) << (24 - 8 * i);
+        }
+
+        let (value, offset, chunk) = *last;
+        let bits = 32 - 8 * leading.len() as u32;
+        if value >> bits != 0 {
+            return Err(ParseError::OutOfRange { position: format!("byte {}", leading.len()), value: chunk.to_string(), offset });
+        }
+
+        result |= value as u32;
+
+        Ok(InetAton {
+            address: IPAddress::from_u32(result),
+            form: InetAtonForm { parts: values.len(), radixes },
+        })
+    }
+
+    /// Converts an IP address into its 32 bit integer form, e.g. `167772161`
+    pub fn to_integer_string(&self) -> String {
+        self.to_u32().to_string()
+    }
+
+    /// Converts an IP address into its 32 bit hexadecimal form, e.g. `0x0a000001`
+    pub fn to_hex_string(&self) -> String {
+        format!("{:#010x}", self.to_u32())
+    }
+
+    /// Converts an IP address into dotted hexadecimal form, e.g. `0x0a.0x00.0x00.0x01`
+    pub fn to_dotted_hex_string(&self) -> String {
+        format!("{:#04x}.{:#04x}.{:#04x}.{:#04x}", self.b0, self.b1, self.b2, self.b3)
+    }
+
+    /// Converts an IP address into dotted octal form, e.g. `012.00.00.01`
+    pub fn to_dotted_octal_string(&self) -> String {
+        format!("0{:o}.0{:o}.0{:o}.0{:o}", self.b0, self.b1, self.b2, self.b3)
+    }
+
+    /// Converts an IP address into dotted binary form, e.g. `00001010.00000000.00000000.00000001`
+    pub fn to_binary_string(&self) -> String {
+        format!("{:08b}.{:08b}.{:08b}.{:08b}", self.b0, self.b1, self.b2, self.b3)
+    }
+}
+
+/// Parses a single inet_aton component, detecting its notation the way glibc
+/// does: `0x` means hexadecimal, any other leading zero means octal
+fn parse_component(token: &str, offset: usize, index: usize) -> Result<(u64, Radix), ParseError> {
+    let position = format!("byte {}", index);
+    if token.is_empty() {
+        return Err(ParseError::EmptyComponent { position, offset });
+    }
+
+    let (digits, radix) = if let Some(digits) = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")) {
+        (digits, Radix::Hex)
+    } else if token.len() > 1 && token.starts_with('0') {
+        (&token[1..], Radix::Octal)
+    } else {
+        (token, Radix::Decimal)
+    };
+
+    let base = match radix {
+        Radix::Decimal => 10,
+        Radix::Octal => 8,
+        Radix::Hex => 16,
+    };
+
+    // Like glibc, a bare `0x` stands for zero
+    let mut value = 0_u64;
+    for b in digits.bytes() {
+        let digit = match (b as char).to_digit(base) {
+            Some(digit) => digit as u64,
+            None => return Err(ParseError::GenericError { position, value: token.to_string(), offset }),
+        };
+
+        value = value * base as u64 + digit;
+        if value > u32::MAX as u64 {
+            return Err(ParseError::OutOfRange { position, value: token.to_string(), offset });
+        }
+    }
+
+    Ok((value, radix))
+}
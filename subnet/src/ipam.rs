@@ -0,0 +1,426 @@
+use custom_error::custom_error;
+use crate::network::{IPNetwork, PrefixLen};
+use crate::types::{IPAddress, NetmaskError};
+
+#[cfg(feature = "persist")]
+use std::{fs, io::Write, path::Path};
+
+/// Version of the persisted IPAM file layout
+#[cfg(feature = "persist")]
+const FILE_VERSION: u32 = 1;
+
+custom_error!{
+    /// Describes an error of the IPAM allocator
+    pub IpamError
+        Overlap{network: String, existing: String} = "Network {network} overlaps {existing}",
+        Duplicate{address: String} = "Address {address} is already reserved",
+        OutsideParent{value: String, parent: String} = "{value} is not within {parent}",
+        NotFound{value: String} = "No pool, subnet or reservation for {value}",
+        InUse{network: String} = "Network {network} still holds allocations",
+        Exhausted{parent: String, what: String} = "No free {what} left in {parent}",
+        Netmask{source: NetmaskError} = "{source}",
+        Io{source: std::io::Error} = "Unable to access IPAM file: {source}",
+        Format{message: String} = "Invalid IPAM file: {message}"
+}
+
+/// Free form information attached to pools and allocations
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct Metadata {
+    /// Team or person owning the allocation
+    pub owner: String,
+    /// What the allocation is used for
+    pub description: String,
+    /// Free form tags, e.g. `prod` or `vlan-20`
+    pub tags: Vec<String>,
+}
+
+/// Host address reserved within a subnet
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reservation {
+    /// Reserved address
+    pub address: IPAddress,
+    /// Information about the reservation
+    #[cfg_attr(feature = "persist", serde(default))]
+    pub metadata: Metadata,
+}
+
+/// Subnet allocated from a pool, holding host reservations
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub struct Subnet {
+    /// Allocated network, host bits cleared
+    pub network: IPNetwork,
+    /// Information about the subnet
+    #[cfg_attr(feature = "persist", serde(default))]
+    pub metadata: Metadata,
+    /// Host reservations, sorted by address
+    #[cfg_attr(feature = "persist", serde(default))]
+    pub hosts: Vec<Reservation>,
+}
+
+/// Supernet subnets are allocated from
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pool {
+    /// Pool network, host bits cleared
+    pub network: IPNetwork,
+    /// Information about the pool
+    #[cfg_attr(feature = "persist", serde(default))]
+    pub metadata: Metadata,
+    /// Allocated subnets, sorted by address
+    #[cfg_attr(feature = "persist", serde(default))]
+    pub subnets: Vec<Subnet>,
+}
+
+/// IP address management: a hierarchy of pools, subnets allocated from them
+/// and hosts reserved in those subnets. Overlapping pools or subnets and
+/// duplicate host reservations are refused.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ipam {
+    /// Pools, sorted by address
+    pools: Vec<Pool>,
+}
+
+/// Layout of persisted IPAM files
+#[cfg(feature = "persist")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct IpamFile {
+    version: u32,
+    #[serde(default)]
+    pools: Vec<Pool>,
+}
+
+impl Metadata {
+    /// Creates new metadata
+    /// 
+    /// Parameters:
+    /// * `owner`: team or person owning the allocation
+    /// * `description`: what the allocation is used for
+    /// * `tags`: free form tags
+    pub fn new(owner: &str, description: &str, tags: &[&str]) -> Metadata {
+        Metadata {
+            owner: owner.to_string(),
+            description: description.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+}
+
+impl Ipam {
+    /// Creates a new empty IPAM
+    pub fn new() -> Ipam {
+        Ipam::default()
+    }
+
+    /// Returns every pool, sorted by address
+    pub fn pools(&self) -> &[Pool] {
+        &self.pools
+    }
+
+    /// Returns the pool with the given network
+    /// 
+    /// Parameters:
+    /// * `network`: network of the pool, host bits are ignored
+    pub fn pool(&self, network: &IPNetwork) -> Option<&Pool> {
+        let network = network.calculate_subnet();
+        self.pools.iter().find(|pool| pool.network == network)
+    }
+
+    /// Returns the allocated subnet with the given network
+    /// 
+    /// Parameters:
+    /// * `network`: network of the subnet, host bits are ignored
+    pub fn subnet(&self, network: &IPNetwork) -> Option<&Subnet> {
+        let network = network.calculate_subnet();
+        self.pools.iter().flat_map(|pool| &pool.subnets).find(|subnet| subnet.network == network)
+    }
+
+    /// Adds a pool
+    /// 
+    /// Parameters:
+    /// * `network`: network of the pool, host bits are ignored
+    /// * `metadata`: information about the pool
+    pub fn add_pool(&mut self, network: &IPNetwork, metadata: Metadata) -> Result<&Pool, IpamError> {
+        let network = network.calculate_subnet();
+        let index = insertion_index(&self.pools, |pool| pool.network, &network)?;
+
+        self.pools.insert(index, Pool { network, metadata, subnets: Vec::new() });
+        Ok(&self.pools[index])
+    }
+
+    /// Removes an empty pool
+    /// 
+    /// Parameters:
+    /// * `network`: network of the pool, host bits are ignored
+    pub fn remove_pool(&mut self, network: &IPNetwork) -> Result<Pool, IpamError> {
+        let index = self.pool_index(network)?;
+        if !self.pools[index].subnets.is_empty() {
+            return Err(IpamError::InUse { network: self.pools[index].network.to_string() });
+        }
+
+        Ok(self.pools.remove(index))
+    }
+
+    /// Allocates the first free subnet with the given CIDR value in a pool
+    /// 
+    /// Parameters:
+    /// * `pool`: network of the pool, host bits are ignored
+    /// * `cidr`: CIDR value of the allocated subnet
+    /// * `metadata`: information about the subnet
+    pub fn allocate_subnet(&mut self, pool: &IPNetwork, cidr: u8, metadata: Metadata) -> Result<IPNetwork, IpamError> {
+        let prefix = PrefixLen::new(cidr)?;
+        let index = self.pool_index(pool)?;
+        let pool = &mut self.pools[index];
+        if cidr < pool.network.cidr() {
+            return Err(NetmaskError::ShorterThanParent { value: cidr, parent: pool.network.cidr() }.into());
+        }
+
+        // Walk the sorted subnets, jumping past every one in the way
+        let (start, end) = pool.network.bounds();
+        let size = 1u64 << (32 - u32::from(cidr));
+        let mut candidate = u64::from(start);
+
+        for subnet in &pool.subnets {
+            let (used_start, used_end) = subnet.network.bounds();
+            if candidate + size <= u64::from(used_start) {
+                break;
+            }
+
+            if u64::from(used_end) >= candidate {
+                candidate = (u64::from(used_end) + 1).div_ceil(size) * size;
+            }
+        }
+
+        if candidate + size - 1 > u64::from(end) {
+            return Err(IpamError::Exhausted { parent: pool.network.to_string(), what: format!("/{} subnet", cidr) });
+        }
+
+        let network = IPNetwork::from_u32(candidate as u32, prefix);
+        let index = insertion_index(&pool.subnets, |subnet| subnet.network, &network)?;
+        pool.subnets.insert(index, Subnet { network, metadata, hosts: Vec::new() });
+
+        Ok(network)
+    }
+
+    /// Allocates a given subnet in the pool holding it
+    /// 
+    /// Parameters:
+    /// * `network`: network of the subnet, host bits are ignored
+    /// * `metadata`: information about the subnet
+    pub fn reserve_subnet(&mut self, network: &IPNetwork, metadata: Metadata) -> Result<IPNetwork, IpamError> {
+        let network = network.calculate_subnet();
+        let pool = match self.pools.iter_mut().find(|pool| pool.network.contains_network(&network)) {
+            Some(pool) => pool,
+            None => return Err(IpamError::OutsideParent { value: network.to_string(), parent: "any pool".to_string() }),
+        };
+
+        let index = insertion_index(&pool.subnets, |subnet| subnet.network, &network)?;
+        pool.subnets.insert(index, Subnet { network, metadata, hosts: Vec::new() });
+
+        Ok(network)
+    }
+
+    /// Releases a subnet along with its host reservations
+    /// 
+    /// Parameters:
+    /// * `network`: network of the subnet, host bits are ignored
+    pub fn release_subnet(&mut self, network: &IPNetwork) -> Result<Subnet, IpamError> {
+        let network = network.calculate_subnet();
+        for pool in &mut self.pools {
+            if let Some(index) = pool.subnets.iter().position(|subnet| subnet.network == network) {
+                return Ok(pool.subnets.remove(index));
+            }
+        }
+
+        Err(IpamError::NotFound { value: network.to_string() })
+    }
+
+    /// Reserves the first free host address of a subnet
+    /// 
+    /// Parameters:
+    /// * `subnet`: network of the subnet, host bits are ignored
+    /// * `metadata`: information about the reservation
+    pub fn allocate_host(&mut self, subnet: &IPNetwork, metadata: Metadata) -> Result<IPAddress, IpamError> {
+        let subnet = self.subnet_mut(subnet)?;
+
+        // Reservations are sorted, so the first gap is the first free host
+        let mut reserved = subnet.hosts.iter().map(|host| host.address).peekable();
+        let mut free = None;
+        for host in subnet.network.hosts() {
+            while reserved.next_if(|address| *address < host).is_some() {}
+            if reserved.peek() != Some(&host) {
+                free = Some(host);
+                break;
+            }
+        }
+
+        let address = match free {
+            Some(address) => address,
+            None => return Err(IpamError::Exhausted { parent: subnet.network.to_string(), what: "host".to_string() }),
+        };
+
+        let index = subnet.hosts.partition_point(|host| host.address < address);
+        subnet.hosts.insert(index, Reservation { address, metadata });
+
+        Ok(address)
+    }
+
+    /// Reserves a given host address in the subnet holding it
+    /// 
+    /// Parameters:
+    /// * `address`: address to reserve, a usable host of its subnet
+    /// * `metadata`: information about the reservation
+    pub fn reserve_host(&mut self, address: &IPAddress, metadata: Metadata) -> Result<IPAddress, IpamError> {
+        let subnet = match self.pools.iter_mut().flat_map(|pool| &mut pool.subnets).find(|subnet| subnet.network.contains(address)) {
+            Some(subnet) => subnet,
+            None => return Err(IpamError::OutsideParent { value: address.to_string(), parent: "any subnet".to_string() }),
+        };
+
+        let summary = subnet.network.summary();
+        if *address < summary.first_host || *address > summary.last_host {
+            return Err(IpamError::OutsideParent { value: address.to_string(), parent: format!("the hosts of {}", subnet.network) });
+        }
+
+        let index = subnet.hosts.partition_point(|host| host.address < *address);
+        if subnet.hosts.get(index).is_some_and(|host| host.address == *address) {
+            return Err(IpamError::Duplicate { address: address.to_string() });
+        }
+
+        subnet.hosts.insert(index, Reservation { address: *address, metadata });
+        Ok(*address)
+    }
+
+    /// Releases a host reservation
+    /// 
+    /// Parameters:
+    /// * `address`: reserved address
+    pub fn release_host(&mut self, address: &IPAddress) -> Result<Reservation, IpamError> {
+        for subnet in self.pools.iter_mut().flat_map(|pool| &mut pool.subnets) {
+            if let Some(index) = subnet.hosts.iter().position(|host| host.address == *address) {
+                return Ok(subnet.hosts.remove(index));
+            }
+        }
+
+        Err(IpamError::NotFound { value: address.to_string() })
+    }
+
+    /// Saves the state to a file, as TOML when its extension is `.toml` and
+    /// as JSON otherwise. The file is replaced atomically: the state is
+    /// written to a temporary file next to it, which is then renamed.
+    /// 
+    /// Parameters:
+    /// * `path`: path of the file
+    #[cfg(feature = "persist")]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), IpamError> {
+        let path = path.as_ref();
+        let file = IpamFile { version: FILE_VERSION, pools: self.pools.clone() };
+        let content = if is_toml(path) {
+            toml::to_string_pretty(&file).map_err(|e| IpamError::Format { message: e.to_string() })?
+        } else {
+            serde_json::to_string_pretty(&file).map_err(|e| IpamError::Format { message: e.to_string() })?
+        };
+
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(format!(".{}.tmp", std::process::id()));
+
+        let result = (|| {
+            let mut output = fs::File::create(&temporary)?;
+            output.write_all(content.as_bytes())?;
+            output.sync_all()?;
+            fs::rename(&temporary, path)
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temporary);
+        }
+
+        Ok(result?)
+    }
+
+    /// Loads the state from a file written by `save`. The state is rebuilt
+    /// allocation by allocation, so that a hand edited file cannot hold
+    /// overlaps.
+    /// 
+    /// Parameters:
+    /// * `path`: path of the file
+    #[cfg(feature = "persist")]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Ipam, IpamError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let file: IpamFile = if is_toml(path) {
+            toml::from_str(&content).map_err(|e| IpamError::Format { message: e.to_string() })?
+        } else {
+            serde_json::from_str(&content).map_err(|e| IpamError::Format { message: e.to_string() })?
+        };
+
+        if file.version != FILE_VERSION {
+            return Err(IpamError::Format { message: format!("unsupported version {}", file.version) });
+        }
+
+        let mut ipam = Ipam::new();
+        for pool in file.pools {
+            ipam.add_pool(&pool.network, pool.metadata)?;
+            for subnet in pool.subnets {
+                if !pool.network.contains_network(&subnet.network) {
+                    return Err(IpamError::OutsideParent { value: subnet.network.to_string(), parent: pool.network.to_string() });
+                }
+
+                ipam.reserve_subnet(&subnet.network, subnet.metadata)?;
+                for host in subnet.hosts {
+                    if !subnet.network.contains(&host.address) {
+                        return Err(IpamError::OutsideParent { value: host.address.to_string(), parent: subnet.network.to_string() });
+                    }
+
+                    ipam.reserve_host(&host.address, host.metadata)?;
+                }
+            }
+        }
+
+        Ok(ipam)
+    }
+
+    /// Returns the index of the pool with the given network
+    fn pool_index(&self, network: &IPNetwork) -> Result<usize, IpamError> {
+        let network = network.calculate_subnet();
+        self.pools.iter().position(|pool| pool.network == network).ok_or(IpamError::NotFound { value: network.to_string() })
+    }
+
+    /// Returns the allocated subnet with the given network, mutably
+    fn subnet_mut(&mut self, network: &IPNetwork) -> Result<&mut Subnet, IpamError> {
+        let network = network.calculate_subnet();
+        self.pools
+            .iter_mut()
+            .flat_map(|pool| &mut pool.subnets)
+            .find(|subnet| subnet.network == network)
+            .ok_or(IpamError::NotFound { value: network.to_string() })
+    }
+}
+
+/// Finds where a network goes in a list sorted by network, refusing overlaps
+/// with its neighbours
+/// 
+/// Parameters:
+/// * `items`: sorted list
+/// * `key`: network of an item
+/// * `network`: network to insert
+fn insertion_index<T>(items: &[T], key: impl Fn(&T) -> IPNetwork, network: &IPNetwork) -> Result<usize, IpamError> {
+    let index = items.partition_point(|item| key(item) < *network);
+
+    for neighbour in [index.checked_sub(1), Some(index)].into_iter().flatten() {
+        if let Some(existing) = items.get(neighbour).map(&key) {
+            if existing.overlaps(network) {
+                return Err(IpamError::Overlap { network: network.to_string(), existing: existing.to_string() });
+            }
+        }
+    }
+
+    Ok(index)
+}
+
+/// Checks whether a file holds TOML, from its extension
+#[cfg(feature = "persist")]
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("toml"))
+}
@@ -0,0 +1,184 @@
+use std::net::Ipv6Addr;
+use crate::constants::{MAX_CIDR, MAX_CIDR_V6};
+use crate::network::{IPNetwork, IPv6Network, PrefixLen};
+use crate::types::{IPAddress, IPv6Address, NetmaskError};
+
+/// Walks evenly spaced 32 bit values, indexed from the start of a block so
+/// that both ends and `nth` jumps are plain arithmetic
+#[derive(Clone, Copy, Debug)]
+struct Steps {
+    /// First value of the walk
+    base: u32,
+    /// Distance between two values, as a power of two
+    shift: u8,
+    /// Index of the next value from the front
+    front: u64,
+    /// Index past the next value from the back
+    back: u64,
+}
+
+impl Steps {
+    fn value(&self, index: u64) -> u32 {
+        self.base.wrapping_add((index << self.shift) as u32)
+    }
+
+    fn len(&self) -> usize {
+        (self.back - self.front) as usize
+    }
+
+    fn next(&mut self) -> Option<u32> {
+        self.nth(0)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<u32> {
+        self.front = self.front.saturating_add(n as u64).min(self.back);
+        if self.front == self.back {
+            return None;
+        }
+
+        self.front += 1;
+        Some(self.value(self.front - 1))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<u32> {
+        self.back = self.back.saturating_sub(n as u64).max(self.front);
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        Some(self.value(self.back))
+    }
+}
+
+/// Iterator over every usable host address of a subnet
+#[derive(Clone, Copy, Debug)]
+pub struct HostIter {
+    steps: Steps,
+}
+
+/// Iterator over every subnet of a given CIDR inside a network
+#[derive(Clone, Copy, Debug)]
+pub struct SubnetIter {
+    steps: Steps,
+    prefix: PrefixLen,
+}
+
+/// Iterator over every host address of an IPv6 subnet
+#[derive(Clone, Copy, Debug)]
+pub struct IPv6HostIter {
+    /// Next address from the front, none once exhausted
+    next: Option<u128>,
+    /// Last address
+    last: u128,
+}
+
+impl IPNetwork {
+    /// Returns an iterator over every usable host address in the subnet of this
+    /// network
+    pub fn hosts(&self) -> HostIter {
+        let summary = self.summary();
+
+        HostIter {
+            steps: Steps { base: summary.first_host.to_u32(), shift: 0, front: 0, back: summary.usable_hosts },
+        }
+    }
+
+    /// Returns an iterator over every subnet with the given CIDR value inside
+    /// the subnet of this network, e.g. every /24 inside a /16
+    /// 
+    /// Parameters:
+    /// * `cidr`: CIDR value of yielded subnets
+    pub fn subnets(&self, cidr: u8) -> Result<SubnetIter, NetmaskError> {
+        let network = self.calculate_subnet();
+        let prefix = PrefixLen::new(cidr)?;
+
+        if cidr < self.cidr() {
+            return Err(NetmaskError::ShorterThanParent { value: cidr, parent: self.cidr() });
+        }
+
+        Ok(SubnetIter {
+            steps: Steps { base: network.address.to_u32(), shift: MAX_CIDR - cidr, front: 0, back: 1 << (cidr - self.cidr()) },
+            prefix,
+        })
+    }
+}
+
+impl IPv6Network {
+    /// Returns an iterator over every host address in the subnet of this
+    /// network. The first address, the subnet-router anycast address, is
+    /// skipped except for /127 and /128 networks (RFC 6164).
+    pub fn hosts(&self) -> IPv6HostIter {
+        let first = u128::from(Ipv6Addr::from(self.calculate_subnet().address));
+        let size = if self.cidr() == 0 { u128::MAX } else { (1u128 << (MAX_CIDR_V6 - self.cidr())) - 1 };
+        let skip = if self.cidr() < MAX_CIDR_V6 - 1 { 1 } else { 0 };
+
+        IPv6HostIter { next: Some(first + skip), last: first + size }
+    }
+}
+
+impl Iterator for IPv6HostIter {
+    type Item = IPv6Address;
+
+    fn next(&mut self) -> Option<IPv6Address> {
+        let value = self.next?;
+        self.next = if value < self.last { Some(value + 1) } else { None };
+        Some(IPv6Address::from(Ipv6Addr::from(value)))
+    }
+}
+
+impl Iterator for HostIter {
+    type Item = IPAddress;
+
+    fn next(&mut self) -> Option<IPAddress> {
+        self.steps.next().map(IPAddress::from_u32)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<IPAddress> {
+        self.steps.nth(n).map(IPAddress::from_u32)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.steps.len(), Some(self.steps.len()))
+    }
+}
+
+impl DoubleEndedIterator for HostIter {
+    fn next_back(&mut self) -> Option<IPAddress> {
+        self.nth_back(0)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<IPAddress> {
+        self.steps.nth_back(n).map(IPAddress::from_u32)
+    }
+}
+
+impl ExactSizeIterator for HostIter {}
+
+impl Iterator for SubnetIter {
+    type Item = IPNetwork;
+
+    fn next(&mut self) -> Option<IPNetwork> {
+        self.steps.next().map(|v| IPNetwork::from_u32(v, self.prefix))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<IPNetwork> {
+        self.steps.nth(n).map(|v| IPNetwork::from_u32(v, self.prefix))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.steps.len(), Some(self.steps.len()))
+    }
+}
+
+impl DoubleEndedIterator for SubnetIter {
+    fn next_back(&mut self) -> Option<IPNetwork> {
+        self.nth_back(0)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<IPNetwork> {
+        self.steps.nth_back(n).map(|v| IPNetwork::from_u32(v, self.prefix))
+    }
+}
+
+impl ExactSizeIterator for SubnetIter {}
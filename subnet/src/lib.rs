@@ -1,14 +1,54 @@
+pub mod aggregate;
+pub mod classful;
+pub mod cloud;
 pub mod constants;
+pub mod convert;
+pub mod dns;
+pub mod inet_aton;
+pub mod ipam;
+pub mod iter;
+pub mod lint;
+pub mod network;
+pub mod parse;
+pub mod range;
+pub mod relation;
+pub mod report;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod set;
+pub mod special;
+pub mod split;
+pub mod summary;
+pub mod trie;
 pub mod types;
+pub mod vlsm;
 
 #[cfg(test)]
 mod tests {
-    use crate::{types::{IPAddress, SubnetMask}, constants::UNDEF_CIDR};
+    use std::collections::{BTreeSet, HashMap};
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::str::FromStr;
+    use crate::types::{IPAddress, IPv6Address, IPv6SubnetMask, NetmaskError, ParseError, SubnetMask, WildcardMask};
+    use crate::aggregate::{aggregate, supernet};
+    use crate::classful::{AddressClass, ClassfulRelation};
+    use crate::cloud::{CloudError, Provider};
+    use crate::dns::{to_bind, to_octodns, ForwardOptions, NameTemplate, TemplateError};
+    use crate::ipam::{Ipam, IpamError, Metadata};
+    use crate::inet_aton::{InetAtonForm, Radix};
+    use crate::lint::{lint, PlanEntry, Severity};
+    use crate::network::{IPNetwork, IPv6Network, IPv6PrefixLen, PrefixLen};
+    use crate::parse::ParseOptions;
+    use crate::range::{IPRange, RangeError};
+    use crate::report::{Format, Report, REPORT_VERSION};
+    use crate::set::IpSet;
+    use crate::special::SpecialRegistry;
+    use crate::trie::PrefixTrie;
+    use crate::vlsm::{HostRequirement, VlsmError};
 
     #[test]
     fn ip_from_string_with_cidr() {
         let address = "192.168.1.2/24".to_string();
-        let ip = IPAddress::from_string(address.to_string());
+        let ip = IPNetwork::from_string(address.to_string());
         
         // Assert that it did not fail
         assert!(ip.is_ok());
@@ -16,18 +56,21 @@ mod tests {
         // If it did not fail, now check values
         let ip = ip.unwrap();
 
-        assert_eq!(192, ip.b0);
-        assert_eq!(168, ip.b1);
-        assert_eq!(1, ip.b2);
-        assert_eq!(2, ip.b3);
-        assert_eq!(24, ip.cidr);
+        assert_eq!(192, ip.address.b0);
+        assert_eq!(168, ip.address.b1);
+        assert_eq!(1, ip.address.b2);
+        assert_eq!(2, ip.address.b3);
+        assert_eq!(24, ip.cidr());
+
+        // A bare address does not take a CIDR value
+        assert!(IPAddress::from_string(address).is_err());
     }
 
     #[test]
     fn wrong_ip_from_string_with_cidr() {
         let address = "192.i68.1.2/24".to_string();
 
-        let ip = IPAddress::from_string(address.to_string());
+        let ip = IPNetwork::from_string(address.to_string());
         
         // Assert that it failed
         assert!(ip.is_err());
@@ -48,24 +91,23 @@ mod tests {
         assert_eq!(168, ip.b1);
         assert_eq!(1, ip.b2);
         assert_eq!(2, ip.b3);
-        assert_eq!(UNDEF_CIDR, ip.cidr);
     }
 
     #[test]
     fn ip_from_str_slice_with_cidr() {
         let address = "192.168.1.2/24";
-        let ip = IPAddress::from_str(address);
+        let ip = IPNetwork::from_str(address);
         // Assert for failure
         assert!(ip.is_ok());
 
         // If it did not fail, now check values
         let ip = ip.unwrap();
 
-        assert_eq!(192, ip.b0);
-        assert_eq!(168, ip.b1);
-        assert_eq!(1, ip.b2);
-        assert_eq!(2, ip.b3);
-        assert_eq!(24, ip.cidr);
+        assert_eq!(IPAddress::new(192, 168, 1, 2), ip.address);
+        assert_eq!(24, ip.prefix.get());
+
+        // A network requires a CIDR value
+        assert!(matches!(IPNetwork::from_str("192.168.1.2"), Err(ParseError::MissingComponent { offset: 11, .. })));
     }
 
     #[test]
@@ -82,12 +124,11 @@ mod tests {
         assert_eq!(168, ip.b1);
         assert_eq!(1, ip.b2);
         assert_eq!(2, ip.b3);
-        assert_eq!(UNDEF_CIDR, ip.cidr);
     }
 
     #[test]
     fn ip_to_string_without_cidr() {
-        let ip = IPAddress::new_without_cidr(192, 168, 1, 243);
+        let ip = IPAddress::new(192, 168, 1, 243);
         let string = ip.to_string();
 
         assert_eq!(string, "192.168.1.243");
@@ -95,7 +136,7 @@ mod tests {
 
     #[test]
     fn ip_to_string_with_cidr() {
-        let ip = IPAddress::new(192, 168, 1, 243, 24);
+        let ip = IPNetwork::new(IPAddress::new(192, 168, 1, 243), PrefixLen::new(24).unwrap());
         let string = ip.to_string();
 
         assert_eq!(string, "192.168.1.243/24");
@@ -136,20 +177,17 @@ mod tests {
     #[test]
     fn subnet_calculation_correct_ip() {
         let address = "192.168.1.2/24";
-        let ip = IPAddress::from_str(address).unwrap();
+        let ip = IPNetwork::from_str(address).unwrap();
 
+        // Networks always have a valid CIDR value, this cannot fail
         let subnet = ip.calculate_subnet();
 
-        // Assert that it did not fail
-        assert!(subnet.is_ok());
-        let subnet = subnet.unwrap();
-
         // Check values
-        assert_eq!(192, subnet.b0);
-        assert_eq!(168, subnet.b1);
-        assert_eq!(1, subnet.b2);
-        assert_eq!(0, subnet.b3);
-        assert_eq!(24, subnet.cidr);
+        assert_eq!(192, subnet.address.b0);
+        assert_eq!(168, subnet.address.b1);
+        assert_eq!(1, subnet.address.b2);
+        assert_eq!(0, subnet.address.b3);
+        assert_eq!(24, subnet.cidr());
     }
 
     #[test]
@@ -168,6 +206,1243 @@ mod tests {
 
         // Convert and check CIDR
         let cidr = subnet.to_cidr();
-        assert_eq!(16, cidr);
+        assert!(cidr.is_ok());
+        assert_eq!(16, cidr.unwrap());
+    }
+
+    #[test]
+    fn ipv6_from_str_compressed() {
+        let ip = IPv6Network::from_str("2001:db8::1/64");
+        // Assert that it did not fail
+        assert!(ip.is_ok());
+
+        let ip = ip.unwrap();
+        assert_eq!([0x2001, 0xdb8, 0, 0, 0, 0, 0, 1], ip.address.segments);
+        assert_eq!(64, ip.cidr());
+    }
+
+    #[test]
+    fn ipv6_from_str_embedded_ipv4() {
+        let ip = IPv6Address::from_str("::ffff:192.168.1.2").unwrap();
+
+        assert_eq!([0, 0, 0, 0, 0, 0xffff, 0xc0a8, 0x0102], ip.segments);
+        assert_eq!("::ffff:192.168.1.2", ip.to_string());
+    }
+
+    #[test]
+    fn wrong_ipv6_from_str() {
+        assert!(IPv6Address::from_str("2001:db8::1::1").is_err());
+        assert!(IPv6Address::from_str("1:2:3:4:5:6:7").is_err());
+        assert!(IPv6Address::from_str("1:2:3:4:5:6:7:8:9").is_err());
+        assert!(IPv6Address::from_str("1:2:3:4::5:6:7:8").is_err());
+        assert!(IPv6Address::from_str("2001:db8::g").is_err());
+        assert!(IPv6Address::from_str("::1.2.3.4:1").is_err());
+        assert!(IPv6Network::from_str("::1/129").is_err());
+        assert!(IPv6Address::from_str("::1/128").is_err());
+    }
+
+    #[test]
+    fn ipv6_to_string_canonical() {
+        let ip = IPv6Network::new(IPv6Address::new([0x2001, 0xdb8, 0, 0, 1, 0, 0, 1]), IPv6PrefixLen::new(48).unwrap());
+        assert_eq!("2001:db8::1:0:0:1/48", ip.to_string());
+
+        let ip = IPv6Address::new([0x2001, 0xdb8, 0, 1, 1, 1, 1, 1]);
+        assert_eq!("2001:db8:0:1:1:1:1:1", ip.to_string());
+
+        let ip = IPv6Address::new([0; 8]);
+        assert_eq!("::", ip.to_string());
+    }
+
+    #[test]
+    fn ipv6_subnet_calculation() {
+        let ip = IPv6Network::from_str("2001:db8:abcd:1234::1/36").unwrap();
+        let subnet = ip.calculate_subnet();
+
+        assert_eq!("2001:db8:a000::/36", subnet.to_string());
+    }
+
+    #[test]
+    fn ipv6_netmask_cidr_roundtrip() {
+        let netmask = IPv6SubnetMask::from_cidr(56).unwrap();
+        assert_eq!("ffff:ffff:ffff:ff00::", netmask.to_string());
+        assert_eq!(56, netmask.to_cidr().unwrap());
+
+        let netmask = IPv6SubnetMask::from_str("ffff:ffff::").unwrap();
+        assert_eq!(32, netmask.to_cidr().unwrap());
+        assert!(IPv6SubnetMask::from_cidr(129).is_err());
+        assert_eq!(netmask, IPv6PrefixLen::new(32).unwrap().netmask());
+    }
+
+    #[test]
+    fn subnet_summary() {
+        let ip = IPNetwork::from_str("192.168.1.77/26").unwrap();
+        let summary = ip.summary();
+
+        assert_eq!("192.168.1.77", summary.address.to_string());
+        assert_eq!("192.168.1.64/26", summary.network.to_string());
+        assert_eq!("255.255.255.192", summary.netmask.to_string());
+        assert_eq!("0.0.0.63", summary.wildcard.to_string());
+        assert_eq!("192.168.1.127", summary.broadcast.unwrap().to_string());
+        assert_eq!("192.168.1.65", summary.first_host.to_string());
+        assert_eq!("192.168.1.126", summary.last_host.to_string());
+        assert_eq!(64, summary.total_hosts);
+        assert_eq!(62, summary.usable_hosts);
+    }
+
+    #[test]
+    fn subnet_summary_point_to_point_and_host() {
+        // RFC 3021: both addresses of a /31 are usable, no broadcast
+        let summary = IPNetwork::from_str("10.0.0.1/31").unwrap().summary();
+        assert!(summary.broadcast.is_none());
+        assert_eq!("10.0.0.0", summary.first_host.to_string());
+        assert_eq!("10.0.0.1", summary.last_host.to_string());
+        assert_eq!(2, summary.usable_hosts);
+
+        let summary = IPNetwork::from_str("10.0.0.1/32").unwrap().summary();
+        assert!(summary.broadcast.is_none());
+        assert_eq!("10.0.0.1", summary.first_host.to_string());
+        assert_eq!("10.0.0.1", summary.last_host.to_string());
+        assert_eq!(1, summary.total_hosts);
+        assert_eq!(1, summary.usable_hosts);
+    }
+
+    #[test]
+    fn subnet_summary_whole_space() {
+        let summary = IPNetwork::from_str("1.2.3.4/0").unwrap().summary();
+        assert_eq!(1 << 32, summary.total_hosts);
+        assert_eq!("0.0.0.0/0", summary.network.to_string());
+        assert_eq!("255.255.255.255", summary.broadcast.unwrap().to_string());
+    }
+
+    #[test]
+    fn prefix_len_validation() {
+        // Invalid prefix lengths cannot be represented
+        assert!(matches!(PrefixLen::new(33), Err(NetmaskError::MaxCidrExceeded { value: 33 })));
+        assert!(PrefixLen::try_from(250).is_err());
+        assert!(IPNetwork::with_cidr(IPAddress::new(10, 0, 0, 1), 99).is_err());
+        assert!(IPv6PrefixLen::new(129).is_err());
+
+        let prefix = PrefixLen::try_from(20).unwrap();
+        assert_eq!(20, u8::from(prefix));
+        assert_eq!("255.255.240.0", prefix.netmask().to_string());
+        assert_eq!("0.0.15.255", prefix.wildcard().to_string());
+        assert_eq!(32, PrefixLen::MAX.get());
+        assert_eq!(128, IPv6PrefixLen::MAX.get());
+
+        // A bare address converts into its single host network
+        assert_eq!("10.0.0.1/32", IPNetwork::from(IPAddress::new(10, 0, 0, 1)).to_string());
+    }
+
+    #[test]
+    fn host_iterator() {
+        let ip = IPNetwork::from_str("192.168.1.2/29").unwrap();
+        let hosts: Vec<String> = ip.hosts().map(|h| h.to_string()).collect();
+
+        assert_eq!(6, hosts.len());
+        assert_eq!("192.168.1.1", hosts[0]);
+        assert_eq!("192.168.1.6", hosts[5]);
+
+        // Walk from both ends
+        let mut hosts = ip.hosts();
+        assert_eq!("192.168.1.6", hosts.next_back().unwrap().to_string());
+        assert_eq!("192.168.1.1", hosts.next().unwrap().to_string());
+        assert_eq!(4, hosts.len());
+    }
+
+    #[test]
+    fn host_iterator_skips_large_ranges() {
+        let ip = IPNetwork::from_str("10.0.0.0/8").unwrap();
+        let mut hosts = ip.hosts();
+
+        assert_eq!(16777214, hosts.len());
+        assert_eq!("10.1.0.0", hosts.nth(65535).unwrap().to_string());
+        assert_eq!("10.255.255.254", hosts.nth_back(0).unwrap().to_string());
+        assert_eq!("10.255.255.253", hosts.next_back().unwrap().to_string());
+        assert_eq!(16777214 - 65536 - 2, hosts.len());
+        assert!(hosts.nth(usize::MAX).is_none());
+    }
+
+    #[test]
+    fn subnet_iterator() {
+        let ip = IPNetwork::from_str("172.16.0.0/16").unwrap();
+        let mut subnets = ip.subnets(24).unwrap();
+
+        assert_eq!(256, subnets.len());
+        assert_eq!("172.16.0.0/24", subnets.next().unwrap().to_string());
+        assert_eq!("172.16.10.0/24", subnets.nth(9).unwrap().to_string());
+        assert_eq!("172.16.255.0/24", subnets.next_back().unwrap().to_string());
+
+        // Whole address space split in two
+        let ip = IPNetwork::from_str("0.0.0.0/0").unwrap();
+        let halves: Vec<String> = ip.subnets(1).unwrap().map(|s| s.to_string()).collect();
+        assert_eq!(vec!["0.0.0.0/1", "128.0.0.0/1"], halves);
+
+        assert!(ip.subnets(33).is_err());
+        assert!(IPNetwork::from_str("10.0.0.0/16").unwrap().subnets(8).is_err());
+    }
+
+    #[test]
+    fn split_by_cidr() {
+        let ip = IPNetwork::from_str("192.168.1.77/24").unwrap();
+        let subnets = ip.split(26);
+
+        // Assert that it did not fail
+        assert!(subnets.is_ok());
+        let subnets: Vec<String> = subnets.unwrap().iter().map(|s| s.to_string()).collect();
+
+        assert_eq!(vec!["192.168.1.0/26", "192.168.1.64/26", "192.168.1.128/26", "192.168.1.192/26"], subnets);
+    }
+
+    #[test]
+    fn split_by_count() {
+        let ip = IPNetwork::from_str("10.0.0.0/16").unwrap();
+
+        assert_eq!(19, ip.split_cidr(5).unwrap());
+        assert_eq!(16, ip.split_cidr(1).unwrap());
+
+        let subnets = ip.split_into(5).unwrap();
+        assert_eq!(8, subnets.len());
+        assert_eq!("10.0.224.0/19", subnets[7].to_string());
+    }
+
+    #[test]
+    fn split_errors() {
+        let ip = IPNetwork::from_str("10.0.0.0/16").unwrap();
+
+        assert!(matches!(ip.split(8), Err(NetmaskError::ShorterThanParent { value: 8, parent: 16 })));
+        assert!(matches!(ip.split(33), Err(NetmaskError::MaxCidrExceeded { value: 33 })));
+        assert!(matches!(ip.split_into(0), Err(NetmaskError::InvalidSubnetCount { count: 0 })));
+        assert!(matches!(ip.split_into(1 << 17), Err(NetmaskError::MaxCidrExceeded { value: 33 })));
+    }
+
+    #[test]
+    fn vlsm_plan() {
+        let parent = IPNetwork::from_str("192.168.10.0/24").unwrap();
+        let requirements = vec![
+            HostRequirement::new("p2p", 2),
+            HostRequirement::from_str("sales: 120 hosts").unwrap(),
+            HostRequirement::from_str("dmz:10").unwrap(),
+        ];
+
+        let plan = parent.vlsm(&requirements);
+        // Assert that it did not fail
+        assert!(plan.is_ok());
+        let plan = plan.unwrap();
+
+        assert_eq!("sales", plan[0].name);
+        assert_eq!("192.168.10.0/25", plan[0].network.to_string());
+        assert_eq!("255.255.255.128", plan[0].netmask.to_string());
+        assert_eq!("192.168.10.127", plan[0].broadcast.unwrap().to_string());
+        assert_eq!(6, plan[0].spare_hosts);
+
+        assert_eq!("dmz", plan[1].name);
+        assert_eq!("192.168.10.128/28", plan[1].network.to_string());
+        assert_eq!(4, plan[1].spare_hosts);
+
+        assert_eq!("p2p", plan[2].name);
+        assert_eq!("192.168.10.144/31", plan[2].network.to_string());
+        assert!(plan[2].broadcast.is_none());
+        assert_eq!(0, plan[2].spare_hosts);
+    }
+
+    #[test]
+    fn vlsm_plan_does_not_fit() {
+        let parent = IPNetwork::from_str("10.0.0.0/26").unwrap();
+        let requirements = vec![HostRequirement::new("a", 30), HostRequirement::new("b", 30), HostRequirement::new("c", 2)];
+
+        match parent.vlsm(&requirements) {
+            Err(VlsmError::DoesNotFit { name, hosts }) => {
+                assert_eq!("c", name);
+                assert_eq!(2, hosts);
+            }
+            _ => panic!("Expected requirement 'c' not to fit"),
+        }
+
+        assert!(HostRequirement::from_str("sales").is_err());
+        assert!(HostRequirement::from_str("sales: many").is_err());
+        assert_eq!(HostRequirement::new("p2p", 1), HostRequirement::from_str("p2p: 1 host").unwrap());
+        assert!(matches!(HostRequirement::from_str("a: 0 hosts"), Err(ParseError::OutOfRange { offset: 3, .. })));
+    }
+
+    #[test]
+    fn aggregate_networks() {
+        let networks: Vec<IPNetwork> = ["10.0.0.0/24", "10.0.1.0/24", "10.0.2.0/23", "10.0.1.128/25", "10.0.5.3/24", "192.168.0.0/16", "0.0.0.0/32"]
+            .iter()
+            .map(|n| IPNetwork::from_str(n).unwrap())
+            .collect();
+
+        let aggregated: Vec<String> = aggregate(&networks).iter().map(|n| n.to_string()).collect();
+        assert_eq!(vec!["0.0.0.0/32", "10.0.0.0/22", "10.0.5.0/24", "192.168.0.0/16"], aggregated);
+    }
+
+    #[test]
+    fn aggregate_whole_space() {
+        let networks = vec![IPNetwork::from_str("0.0.0.0/1").unwrap(), IPNetwork::from_str("128.0.0.0/1").unwrap()];
+        let aggregated = aggregate(&networks);
+
+        assert_eq!(1, aggregated.len());
+        assert_eq!("0.0.0.0/0", aggregated[0].to_string());
+    }
+
+    #[test]
+    fn supernet_of_networks() {
+        let networks = vec![IPNetwork::from_str("10.0.0.0/24").unwrap(), IPNetwork::from_str("10.0.3.0/24").unwrap()];
+        let summary = supernet(&networks);
+
+        // Assert that it did not fail
+        assert!(summary.is_ok());
+        let summary = summary.unwrap();
+
+        assert_eq!("10.0.0.0/22", summary.network.to_string());
+        assert_eq!(512, summary.extra_addresses);
+
+        assert!(matches!(supernet(&[]), Err(NetmaskError::NoNetworks)));
+    }
+
+    #[test]
+    fn network_contains() {
+        let network = IPNetwork::from_str("10.1.0.0/16").unwrap();
+
+        assert!(network.contains(&IPAddress::from_str("10.1.200.3").unwrap()));
+        assert!(!network.contains(&IPAddress::from_str("10.2.0.0").unwrap()));
+        assert!(network.contains_network(&IPNetwork::from_str("10.1.4.0/24").unwrap()));
+        assert!(!network.contains_network(&IPNetwork::from_str("10.0.0.0/8").unwrap()));
+
+        // Host bits of the network are ignored
+        let network = IPNetwork::from_str("10.1.2.3/16").unwrap();
+        assert!(IPNetwork::from_str("10.1.4.0/24").unwrap().is_subnet_of(&network));
+        assert!(!IPNetwork::from(IPAddress::new(10, 1, 0, 0)).contains_network(&network));
+    }
+
+    #[test]
+    fn network_overlaps_and_adjacency() {
+        let a = IPNetwork::from_str("192.168.0.0/23").unwrap();
+        let b = IPNetwork::from_str("192.168.1.0/24").unwrap();
+        let c = IPNetwork::from_str("192.168.2.0/24").unwrap();
+
+        assert!(a.overlaps(&b));
+        assert!(b.overlaps(&a));
+        assert!(!a.overlaps(&c));
+
+        assert!(a.is_adjacent(&c));
+        assert!(c.is_adjacent(&b));
+        assert!(!a.is_adjacent(&b));
+
+        let last = IPNetwork::from_str("255.255.255.0/24").unwrap();
+        let first = IPNetwork::from_str("0.0.0.0/24").unwrap();
+        assert!(!last.is_adjacent(&first));
+    }
+
+    #[test]
+    fn network_ordering() {
+        let mut networks: Vec<IPNetwork> = ["10.0.1.0/24", "10.0.0.0/24", "10.0.0.0/16", "9.0.0.1/32", "10.0.0.1/8"]
+            .iter()
+            .map(|n| IPNetwork::from_str(n).unwrap())
+            .collect();
+
+        networks.sort_by(|a, b| a.network_cmp(b));
+        let networks: Vec<String> = networks.iter().map(|n| n.to_string()).collect();
+
+        assert_eq!(vec!["9.0.0.1/32", "10.0.0.1/8", "10.0.0.0/16", "10.0.0.0/24", "10.0.1.0/24"], networks);
+    }
+
+    #[test]
+    fn range_from_str() {
+        let range = IPRange::from_str("10.0.0.5 - 10.0.3.200");
+        // Assert that it did not fail
+        assert!(range.is_ok());
+
+        let range = range.unwrap();
+        assert_eq!("10.0.0.5", range.start.to_string());
+        assert_eq!("10.0.3.200", range.end.to_string());
+        assert_eq!(964, range.len());
+        assert_eq!("10.0.0.5-10.0.3.200", range.to_string());
+
+        assert!(IPRange::from_str("10.0.0.5-10.0.3.200").is_ok());
+        match IPRange::from_str("10.0.3.200-10.0.0.5") {
+            Err(RangeError::Reversed { start, end }) => assert_eq!(("10.0.3.200", "10.0.0.5"), (start.as_str(), end.as_str())),
+            _ => panic!("Expected reversed range error"),
+        }
+        assert!(matches!(IPRange::from_str("10.0.0.5"), Err(RangeError::Parse { source: ParseError::MissingComponent { offset: 8, .. } })));
+    }
+
+    #[test]
+    fn range_to_cidrs_and_back() {
+        let range = IPRange::from_str("10.0.0.5-10.0.3.200").unwrap();
+        let cidrs: Vec<String> = range.to_cidrs().iter().map(|c| c.to_string()).collect();
+
+        assert_eq!(vec![
+            "10.0.0.5/32", "10.0.0.6/31", "10.0.0.8/29", "10.0.0.16/28", "10.0.0.32/27", "10.0.0.64/26", "10.0.0.128/25",
+            "10.0.1.0/24", "10.0.2.0/24", "10.0.3.0/25", "10.0.3.128/26", "10.0.3.192/29", "10.0.3.200/32",
+        ], cidrs);
+
+        let back = IPRange::from_cidrs(&range.to_cidrs()).unwrap();
+        assert_eq!(range.to_string(), back.to_string());
+
+        let gap = vec![IPNetwork::from_str("10.0.0.0/32").unwrap(), IPNetwork::from_str("10.0.0.2/32").unwrap()];
+        assert!(matches!(IPRange::from_cidrs(&gap), Err(RangeError::NotContiguous)));
+
+        let network = IPRange::from_network(&IPNetwork::from_str("192.168.1.9/24").unwrap());
+        assert_eq!("192.168.1.0-192.168.1.255", network.to_string());
+    }
+
+    #[test]
+    fn range_intersection() {
+        let a = IPRange::from_str("10.0.0.0-10.0.0.100").unwrap();
+        let b = IPRange::from_str("10.0.0.50-10.0.1.0").unwrap();
+        let c = IPRange::from_str("10.0.2.0-10.0.2.1").unwrap();
+
+        assert_eq!("10.0.0.50-10.0.0.100", a.intersection(&b).unwrap().to_string());
+        assert!(a.intersection(&c).is_none());
+        assert!(a.contains(&IPAddress::from_str("10.0.0.100").unwrap()));
+        assert!(!a.contains(&IPAddress::from_str("10.0.0.101").unwrap()));
+    }
+
+    #[test]
+    fn set_complement_of_private_space() {
+        let all = IpSet::from_cidrs(&[IPNetwork::from_str("0.0.0.0/0").unwrap()]);
+        let private: Vec<IPNetwork> = ["10.0.0.0/8", "172.16.0.0/12", "192.168.0.0/16"]
+            .iter()
+            .map(|n| IPNetwork::from_str(n).unwrap())
+            .collect();
+        let private = IpSet::from_cidrs(&private);
+
+        let allowed = all.difference(&private);
+        let cidrs: Vec<String> = allowed.cidrs().map(|c| c.to_string()).collect();
+
+        assert_eq!(vec![
+            "0.0.0.0/5", "8.0.0.0/7", "11.0.0.0/8", "12.0.0.0/6", "16.0.0.0/4", "32.0.0.0/3", "64.0.0.0/2", "128.0.0.0/3",
+            "160.0.0.0/5", "168.0.0.0/6", "172.0.0.0/12", "172.32.0.0/11", "172.64.0.0/10", "172.128.0.0/9", "173.0.0.0/8",
+            "174.0.0.0/7", "176.0.0.0/4", "192.0.0.0/9", "192.128.0.0/11", "192.160.0.0/13", "192.169.0.0/16",
+            "192.170.0.0/15", "192.172.0.0/14", "192.176.0.0/12", "192.192.0.0/10", "193.0.0.0/8", "194.0.0.0/7",
+            "196.0.0.0/6", "200.0.0.0/5", "208.0.0.0/4", "224.0.0.0/3",
+        ], cidrs);
+
+        // Complement gives the same result
+        assert_eq!(allowed.len(), private.complement().len());
+        assert_eq!((1_u64 << 32) - private.len(), allowed.len());
+        assert!(!allowed.contains(&IPAddress::from_str("172.20.1.1").unwrap()));
+        assert!(allowed.contains(&IPAddress::from_str("8.8.8.8").unwrap()));
+    }
+
+    #[test]
+    fn set_algebra() {
+        let a = IpSet::from_ranges(&[IPRange::from_str("10.0.0.0-10.0.0.99").unwrap()]);
+        let b = IpSet::from_ranges(&[IPRange::from_str("10.0.0.50-10.0.0.149").unwrap()]);
+        let to_strings = |s: &IpSet| s.ranges().iter().map(|r| r.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(vec!["10.0.0.0-10.0.0.149"], to_strings(&a.union(&b)));
+        assert_eq!(vec!["10.0.0.50-10.0.0.99"], to_strings(&a.intersection(&b)));
+        assert_eq!(vec!["10.0.0.0-10.0.0.49"], to_strings(&a.difference(&b)));
+        assert_eq!(vec!["10.0.0.0-10.0.0.49", "10.0.0.100-10.0.0.149"], to_strings(&a.symmetric_difference(&b)));
+
+        // Adjacent inserts are merged together
+        let mut set = IpSet::new();
+        assert!(set.is_empty());
+        set.insert(&IPNetwork::from_str("10.0.1.0/24").unwrap());
+        set.insert(&IPNetwork::from_str("10.0.0.0/24").unwrap());
+        set.insert_range(&IPRange::from_str("10.0.2.0-10.0.2.0").unwrap());
+        assert_eq!(vec!["10.0.0.0-10.0.2.0"], to_strings(&set));
+        assert_eq!(vec!["0.0.0.0-9.255.255.255", "10.0.2.1-255.255.255.255"], to_strings(&set.complement()));
+        assert!(IpSet::new().complement().complement().is_empty());
+    }
+
+    #[test]
+    fn trie_longest_prefix_match() {
+        let mut table = PrefixTrie::new();
+        for (network, hop) in [("0.0.0.0/0", "default"), ("10.0.0.0/8", "a"), ("10.1.0.0/16", "b"), ("10.1.2.0/24", "c"), ("10.1.3.7/32", "d")] {
+            assert!(table.insert(&IPNetwork::from_str(network).unwrap(), hop).is_none());
+        }
+
+        let lookup = |address: &str| {
+            let (network, hop) = table.longest_match(&IPAddress::from_str(address).unwrap()).unwrap();
+            format!("{} {}", network, hop)
+        };
+
+        assert_eq!(5, table.len());
+        assert_eq!("10.1.2.0/24 c", lookup("10.1.2.200"));
+        assert_eq!("10.1.3.7/32 d", lookup("10.1.3.7"));
+        assert_eq!("10.1.0.0/16 b", lookup("10.1.3.8"));
+        assert_eq!("10.0.0.0/8 a", lookup("10.200.0.1"));
+        assert_eq!("0.0.0.0/0 default", lookup("192.168.1.1"));
+    }
+
+    #[test]
+    fn trie_exact_lookup_and_remove() {
+        let mut table = PrefixTrie::new();
+        let network = IPNetwork::from_str("192.168.0.0/16").unwrap();
+        let subnet = IPNetwork::from_str("192.168.4.0/22").unwrap();
+
+        table.insert(&network, 1);
+        table.insert(&subnet, 2);
+        assert_eq!(Some(1), table.insert(&IPNetwork::from_str("192.168.1.1/16").unwrap(), 3));
+
+        assert_eq!(Some(&3), table.get(&network));
+        assert_eq!(None, table.get(&IPNetwork::from_str("192.168.0.0/17").unwrap()));
+
+        assert_eq!(Some(3), table.remove(&network));
+        assert_eq!(None, table.remove(&network));
+        assert_eq!(1, table.len());
+        assert!(table.longest_match(&IPAddress::from_str("192.168.1.1").unwrap()).is_none());
+        assert_eq!("192.168.4.0/22", table.longest_match(&IPAddress::from_str("192.168.5.1").unwrap()).unwrap().0.to_string());
+    }
+
+    #[test]
+    fn trie_covering_and_covered() {
+        let mut table = PrefixTrie::new();
+        for network in ["10.0.0.0/8", "10.1.0.0/16", "10.1.128.0/17", "10.1.2.0/24", "10.2.0.0/16", "11.0.0.0/8"] {
+            table.insert(&IPNetwork::from_str(network).unwrap(), ());
+        }
+
+        fn names<'a>(entries: impl Iterator<Item = (IPNetwork, &'a ())>) -> Vec<String> {
+            entries.map(|(n, _)| n.to_string()).collect()
+        }
+
+        let covering = table.covering(&IPNetwork::from_str("10.1.2.0/25").unwrap());
+        assert_eq!(vec!["10.0.0.0/8", "10.1.0.0/16", "10.1.2.0/24"], names(covering));
+
+        let covered = table.covered(&IPNetwork::from_str("10.1.0.0/16").unwrap());
+        assert_eq!(vec!["10.1.0.0/16", "10.1.2.0/24", "10.1.128.0/17"], names(covered));
+
+        let covered = table.covered(&IPNetwork::from_str("10.0.0.0/15").unwrap());
+        assert_eq!(vec!["10.1.0.0/16", "10.1.2.0/24", "10.1.128.0/17"], names(covered));
+
+        assert_eq!(6, table.iter().count());
+        assert_eq!(None, table.covered(&IPNetwork::from_str("12.0.0.0/8").unwrap()).next());
+    }
+
+    #[test]
+    fn trie_matches_linear_scan() {
+        // Small xorshift generator, good enough for test data
+        let mut state = 0x2545F491_u32;
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+
+        let mut table = PrefixTrie::new();
+        let mut networks = Vec::new();
+        for i in 0..2000 {
+            let value = random();
+            let network = IPNetwork::from_u32(value, PrefixLen::new((value % 17) as u8 + 8).unwrap()).calculate_subnet();
+            table.insert(&network, i);
+            networks.push(network);
+        }
+
+        // Drop a third of them again
+        for network in networks.iter().step_by(3) {
+            table.remove(network);
+        }
+
+        for _ in 0..500 {
+            let address = IPAddress::from_u32(random());
+            let expected = table
+                .iter()
+                .filter(|(n, _)| n.contains(&address))
+                .max_by_key(|(n, _)| n.prefix)
+                .map(|(n, _)| n.to_string());
+
+            assert_eq!(expected, table.longest_match(&address).map(|(n, _)| n.to_string()));
+        }
+    }
+
+    #[test]
+    #[ignore = "loads a full size table, run with cargo test --release -- --ignored"]
+    fn trie_million_prefixes() {
+        let mut state = 0x2545F491_u32;
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+
+        // Mostly /24s, like a full Internet routing table
+        let mut table = PrefixTrie::new();
+        while table.len() < 1_000_000 {
+            let value = random();
+            let cidr = if value % 4 == 0 { (value % 17) as u8 + 8 } else { 24 };
+            table.insert(&IPNetwork::from_u32(value, PrefixLen::new(cidr).unwrap()), value);
+        }
+
+        let addresses: Vec<IPAddress> = (0..1_000_000).map(|_| IPAddress::from_u32(random())).collect();
+        let start = std::time::Instant::now();
+        let found = addresses.iter().filter(|address| table.longest_match(address).is_some()).count();
+        let per_lookup = start.elapsed() / addresses.len() as u32;
+
+        assert!(found > 0);
+        assert!(per_lookup < std::time::Duration::from_micros(1), "{:?} per lookup", per_lookup);
+    }
+
+    #[test]
+    fn strict_parser_rejects_short_and_empty_input() {
+        assert!(matches!(IPAddress::from_str("10.1"), Err(ParseError::MissingComponent { offset: 4, .. })));
+        assert!(matches!(IPAddress::from_str(""), Err(ParseError::EmptyComponent { offset: 0, .. })));
+        assert!(matches!(IPAddress::from_str("10..0.1"), Err(ParseError::EmptyComponent { offset: 3, .. })));
+        assert!(matches!(IPNetwork::from_str("10.0.0.1/"), Err(ParseError::EmptyComponent { offset: 9, .. })));
+        assert!(matches!(SubnetMask::from_str("255.255"), Err(ParseError::MissingComponent { offset: 7, .. })));
+        assert!(matches!(IPv6Address::from_str("1:2:3"), Err(ParseError::MissingComponent { offset: 5, .. })));
+    }
+
+    #[test]
+    fn strict_parser_rejects_trailing_garbage() {
+        match IPAddress::from_str("1.2.3.4.5") {
+            Err(ParseError::TrailingGarbage { value, offset }) => {
+                assert_eq!(".5", value);
+                assert_eq!(7, offset);
+            }
+            _ => panic!("Expected trailing garbage error"),
+        }
+
+        match IPNetwork::from_str("1.2.3.4/24/7") {
+            Err(ParseError::TrailingGarbage { value, offset }) => {
+                assert_eq!("/7", value);
+                assert_eq!(10, offset);
+            }
+            _ => panic!("Expected trailing garbage error"),
+        }
+
+        assert!(matches!(IPv6Address::from_str("1:2:3:4:5:6:7:8:9"), Err(ParseError::TrailingGarbage { offset: 15, .. })));
+        match IPv6Address::from_str("1::2:3:4:5:6:7:8") {
+            Err(ParseError::TrailingGarbage { value, offset }) => {
+                assert_eq!(":8", value);
+                assert_eq!(14, offset);
+            }
+            _ => panic!("Expected trailing garbage error"),
+        }
+
+        // An embedded IPv4 address takes two segments
+        match IPv6Address::from_str("1:2:3:4:5:6:7:1.2.3.4") {
+            Err(ParseError::TrailingGarbage { value, offset }) => {
+                assert_eq!(":1.2.3.4", value);
+                assert_eq!(13, offset);
+            }
+            _ => panic!("Expected trailing garbage error"),
+        }
+
+        assert!(matches!(IPv6Network::from_str("1:2:3:4:5:6:7:1.2.3.4/64"), Err(ParseError::TrailingGarbage { offset: 13, .. })));
+        assert!(matches!(IPv6Address::from_str("1:2:3:4:5:6::7:1.2.3.4"), Err(ParseError::TrailingGarbage { offset: 14, .. })));
+        assert!(matches!(IPv6Address::from_str("1:2:3:4:5:6:7:8::"), Err(ParseError::TrailingGarbage { offset: 13, .. })));
+        assert!(matches!(IPv6Address::from_str("1:2:3:4:5:6:7::8"), Err(ParseError::TrailingGarbage { offset: 15, .. })));
+        assert_eq!("1:2:3:4:5:0:102:304", IPv6Address::from_str("1:2:3:4:5::1.2.3.4").unwrap().to_string());
+        assert!(matches!(SubnetMask::from_str("255.0.0.0/8"), Err(ParseError::GenericError { offset: 8, .. })));
+        assert!(matches!(IPAddress::from_str("1.2.3.4/24"), Err(ParseError::TrailingGarbage { offset: 7, .. })));
+    }
+
+    #[test]
+    fn strict_parser_rejects_bad_tokens() {
+        match IPAddress::from_str("1.2. 3.4") {
+            Err(ParseError::GenericError { position, value, offset }) => {
+                assert_eq!("byte 2", position);
+                assert_eq!(" 3", value);
+                assert_eq!(4, offset);
+            }
+            _ => panic!("Expected generic error"),
+        }
+
+        assert!(matches!(IPAddress::from_str("+1.2.3.4"), Err(ParseError::GenericError { offset: 0, .. })));
+        assert!(matches!(IPAddress::from_str("1.2.3.-4"), Err(ParseError::GenericError { offset: 6, .. })));
+        assert!(matches!(IPAddress::from_str("1.2.256.4"), Err(ParseError::OutOfRange { offset: 4, .. })));
+        assert!(matches!(IPNetwork::from_str("1.2.3.4/33"), Err(ParseError::MaxCidrExceeded { value: 33, offset: 8 })));
+        assert!(matches!(IPNetwork::from_str("1.2.3.4/99999999999"), Err(ParseError::OutOfRange { offset: 8, .. })));
+        assert!(matches!(IPRange::from_str("10.0.0.1 - 10.0.x.2"), Err(RangeError::Parse { source: ParseError::GenericError { offset: 16, .. } })));
+    }
+
+    #[test]
+    fn strict_parser_leading_zeros() {
+        assert!(matches!(IPAddress::from_str("010.0.0.1"), Err(ParseError::LeadingZero { offset: 0, .. })));
+        assert!(matches!(IPNetwork::from_str("10.0.0.1/08"), Err(ParseError::LeadingZero { offset: 9, .. })));
+
+        let options = ParseOptions { allow_leading_zeros: true, ..ParseOptions::default() };
+        let ip = IPNetwork::from_str_with_options("010.000.0.001/08", &options).unwrap();
+        assert_eq!("10.0.0.1/8", ip.to_string());
+
+        let netmask = SubnetMask::from_str_with_options("255.255.000.000", &options).unwrap();
+        assert_eq!(16, netmask.to_cidr().unwrap());
+    }
+
+    #[test]
+    fn inet_aton_forms() {
+        let parsed = IPAddress::from_inet_aton("10.1");
+        // Assert that it did not fail
+        assert!(parsed.is_ok());
+
+        let parsed = parsed.unwrap();
+        assert_eq!("10.0.0.1", parsed.address.to_string());
+        assert_eq!(InetAtonForm { parts: 2, radixes: vec![Radix::Decimal, Radix::Decimal] }, parsed.form);
+        assert!(!parsed.form.is_standard());
+
+        let parsed = IPAddress::from_inet_aton("0x0a000001").unwrap();
+        assert_eq!("10.0.0.1", parsed.address.to_string());
+        assert_eq!(vec![Radix::Hex], parsed.form.radixes);
+
+        let parsed = IPAddress::from_inet_aton("012.0.0.1").unwrap();
+        assert_eq!("10.0.0.1", parsed.address.to_string());
+        assert_eq!(Radix::Octal, parsed.form.radixes[0]);
+
+        assert_eq!("10.0.0.1", IPAddress::from_inet_aton("167772161").unwrap().address.to_string());
+        assert_eq!("192.168.1.1", IPAddress::from_inet_aton("192.168.257").unwrap().address.to_string());
+        assert!(IPAddress::from_inet_aton("0xA.0/8").is_err());
+        assert!(IPAddress::from_inet_aton("1.2.3.4").unwrap().form.is_standard());
+    }
+
+    #[test]
+    fn inet_aton_rejects_invalid_forms() {
+        assert!(matches!(IPAddress::from_inet_aton("1.2.65536"), Err(ParseError::OutOfRange { offset: 4, .. })));
+        assert!(matches!(IPAddress::from_inet_aton("256.1"), Err(ParseError::OutOfRange { offset: 0, .. })));
+        assert!(matches!(IPAddress::from_inet_aton("4294967296"), Err(ParseError::OutOfRange { offset: 0, .. })));
+        assert!(matches!(IPAddress::from_inet_aton("09.1.1.1"), Err(ParseError::GenericError { offset: 0, .. })));
+        assert!(matches!(IPAddress::from_inet_aton("1.2.3.4.5"), Err(ParseError::TrailingGarbage { offset: 7, .. })));
+        assert!(matches!(IPAddress::from_inet_aton("1..2"), Err(ParseError::EmptyComponent { offset: 2, .. })));
+
+        // Lenient parsing is opt-in
+        assert!(IPAddress::from_str("10.1").is_err());
+        let options = ParseOptions { inet_aton: true, ..ParseOptions::default() };
+        assert_eq!("10.0.0.1", IPAddress::from_str_with_options("10.1", &options).unwrap().to_string());
+        assert_eq!("10.0.0.0/8", IPNetwork::from_str_with_options("0xA.0/8", &options).unwrap().to_string());
+    }
+
+    #[test]
+    fn legacy_formatters() {
+        let ip = IPAddress::new(10, 0, 0, 1);
+
+        assert_eq!("167772161", ip.to_integer_string());
+        assert_eq!("0x0a000001", ip.to_hex_string());
+        assert_eq!("0x0a.0x00.0x00.0x01", ip.to_dotted_hex_string());
+        assert_eq!("012.00.00.01", ip.to_dotted_octal_string());
+        assert_eq!("00001010.00000000.00000000.00000001", ip.to_binary_string());
+
+        // Every form reads back as the same address
+        for form in [ip.to_integer_string(), ip.to_hex_string(), ip.to_dotted_hex_string(), ip.to_dotted_octal_string()] {
+            assert_eq!(ip.to_u32(), IPAddress::from_inet_aton(&form).unwrap().address.to_u32());
+        }
+    }
+
+    #[test]
+    fn netmask_contiguity() {
+        let netmask = SubnetMask::from_str("255.0.255.0").unwrap();
+        assert!(!netmask.is_contiguous());
+        assert!(matches!(netmask.to_cidr(), Err(NetmaskError::NonContiguous { .. })));
+
+        assert_eq!(0, SubnetMask::from_str("0.0.0.0").unwrap().to_cidr().unwrap());
+        assert_eq!(32, SubnetMask::from_str("255.255.255.255").unwrap().to_cidr().unwrap());
+        assert!(SubnetMask::from_str("255.255.255.254").unwrap().to_cidr().is_ok());
+        assert!(SubnetMask::from_str("127.255.255.0").unwrap().to_cidr().is_err());
+        assert!(IPv6SubnetMask::from_str("ffff:0:ffff::").unwrap().to_cidr().is_err());
+    }
+
+    #[test]
+    fn wildcard_conversions() {
+        let wildcard = WildcardMask::from_str("0.0.0.255");
+        // Assert that it did not fail
+        assert!(wildcard.is_ok());
+
+        let wildcard = wildcard.unwrap();
+        assert_eq!("255.255.255.0", wildcard.to_netmask().unwrap().to_string());
+        assert_eq!(24, wildcard.to_cidr().unwrap());
+        assert_eq!("0.0.15.255", WildcardMask::from_cidr(20).unwrap().to_string());
+        assert_eq!("0.0.0.63", SubnetMask::from_cidr(26).unwrap().to_wildcard().to_string());
+
+        // Arbitrary patterns are fine as wildcards, but have no netmask
+        let wildcard = WildcardMask::from_str("0.255.0.255").unwrap();
+        assert!(matches!(wildcard.to_netmask(), Err(NetmaskError::NonContiguous { .. })));
+    }
+
+    #[test]
+    fn wildcard_matching() {
+        // Every x.y.1.z address
+        let base = IPAddress::from_str("0.0.1.0").unwrap();
+        let wildcard = WildcardMask::from_str("255.255.0.255").unwrap();
+
+        assert!(IPAddress::from_str("10.20.1.30").unwrap().matches_wildcard(&base, &wildcard));
+        assert!(!IPAddress::from_str("10.20.2.30").unwrap().matches_wildcard(&base, &wildcard));
+
+        // Odd addresses of 192.168.1.0/24
+        let base = IPAddress::from_str("192.168.1.1").unwrap();
+        let wildcard = WildcardMask::from_str("0.0.0.254").unwrap();
+
+        assert!(IPAddress::from_str("192.168.1.77").unwrap().matches_wildcard(&base, &wildcard));
+        assert!(!IPAddress::from_str("192.168.1.78").unwrap().matches_wildcard(&base, &wildcard));
+    }
+
+    #[test]
+    fn standard_traits() {
+        let ip: IPNetwork = "10.0.0.1/8".parse().unwrap();
+        assert_eq!("10.0.0.1/8", format!("{}", ip));
+        assert_eq!(IPNetwork::with_cidr(IPAddress::new(10, 0, 0, 1), 8).unwrap(), ip);
+        assert_ne!(IPNetwork::with_cidr(IPAddress::new(10, 0, 0, 1), 16).unwrap(), ip);
+
+        let mut hops = HashMap::new();
+        hops.insert(ip, "a");
+        assert_eq!(Some(&"a"), hops.get(&IPNetwork::with_cidr(IPAddress::new(10, 0, 0, 1), 8).unwrap()));
+
+        let address: IPAddress = "10.0.0.1".parse().unwrap();
+        assert_eq!("10.0.0.1", format!("{}", address));
+        assert_eq!(ip.address, address);
+
+        let netmask: SubnetMask = "255.255.255.0".parse().unwrap();
+        assert_eq!("255.255.255.0", format!("{}", netmask));
+        assert_eq!(SubnetMask::from_cidr(24).unwrap(), netmask);
+        assert!(format!("{:?}", netmask).contains("b0: 255"));
+    }
+
+    #[test]
+    fn address_ordering() {
+        let mut networks: Vec<IPNetwork> = ["10.0.0.0/24", "9.255.255.255/32", "10.0.0.0/8", "10.0.0.1/32", "2.0.0.0/8"]
+            .iter()
+            .map(|n| n.parse().unwrap())
+            .collect();
+
+        networks.sort();
+        let sorted: Vec<String> = networks.iter().map(|n| n.to_string()).collect();
+        assert_eq!(vec!["2.0.0.0/8", "9.255.255.255/32", "10.0.0.0/8", "10.0.0.0/24", "10.0.0.1/32"], sorted);
+
+        let set: BTreeSet<IPNetwork> = networks.iter().copied().chain(networks.iter().copied()).collect();
+        assert_eq!(5, set.len());
+
+        let mut addresses: Vec<IPAddress> = networks.iter().map(|n| n.address).collect();
+        addresses.sort();
+        assert_eq!(IPAddress::new(2, 0, 0, 0), addresses[0]);
+    }
+
+    #[test]
+    fn std_conversions() {
+        let ip = IPAddress::from(0x0A000001_u32);
+        assert_eq!("10.0.0.1", ip.to_string());
+        assert_eq!(0x0A000001, u32::from(ip));
+        assert_eq!([10, 0, 0, 1], <[u8; 4]>::from(ip));
+        assert_eq!(ip, IPAddress::from([10, 0, 0, 1]));
+
+        let std_ip = Ipv4Addr::new(192, 168, 1, 1);
+        assert_eq!(std_ip, Ipv4Addr::from(IPAddress::from(std_ip)));
+        assert_eq!(IpAddr::V4(std_ip), IpAddr::from(IPAddress::from(std_ip)));
+        assert_eq!(IPAddress::from(std_ip), IPAddress::try_from(IpAddr::V4(std_ip)).unwrap());
+        assert!(IPAddress::try_from(IpAddr::V6(Ipv6Addr::LOCALHOST)).is_err());
+
+        let netmask = SubnetMask::from(Ipv4Addr::new(255, 255, 0, 0));
+        assert_eq!(16, netmask.to_cidr().unwrap());
+        assert_eq!(0xFFFF0000, u32::from(netmask));
+
+        let ipv6 = IPv6Address::from(Ipv6Addr::LOCALHOST);
+        assert_eq!("::1", ipv6.to_string());
+        assert_eq!(Ipv6Addr::LOCALHOST, Ipv6Addr::from(ipv6));
+        assert!(IPv6Address::try_from(IpAddr::V4(std_ip)).is_err());
+    }
+
+    #[test]
+    fn report_formats() {
+        let summary = IPNetwork::from_str("192.168.1.2/31").unwrap().summary();
+        let report = Report::summary(&summary);
+        assert_eq!(REPORT_VERSION, 2);
+
+        let json = report.render(Format::Json);
+        assert!(json.starts_with("{\"version\":2,\"kind\":\"summary\",\"records\":[{\"address\":\"192.168.1.2\",\"network\":\"192.168.1.2/31\""));
+        assert!(json.contains("\"broadcast\":null,"));
+        assert!(json.contains("\"total_hosts\":2,\"usable_hosts\":2,\"class\":\"C\",\"classful\":\"subnetted (/24 + 7 bits)\",\"type\":\"private\"}]}"));
+
+        let yaml = report.render(Format::Yaml);
+        assert!(yaml.starts_with("version: 2\nkind: summary\nrecords:\n  - address: \"192.168.1.2\"\n    network: \"192.168.1.2/31\"\n"));
+        assert!(yaml.contains("    broadcast: null\n"));
+
+        let csv = report.render(Format::Csv);
+        assert_eq!("address,network,netmask,wildcard,broadcast,first_host,last_host,total_hosts,usable_hosts,class,classful,type\n\
+            192.168.1.2,192.168.1.2/31,255.255.255.254,0.0.0.1,,192.168.1.2,192.168.1.3,2,2,C,subnetted (/24 + 7 bits),private\n", csv);
+        assert_eq!(csv.replace(',', "\t"), report.render(Format::Tsv));
+
+        // Split results are aligned in a table
+        let networks = IPNetwork::from_str("10.0.0.0/24").unwrap().split(25).unwrap();
+        let table = Report::networks("split", &networks).render(Format::Table);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(3, lines.len());
+        assert_eq!("network        netmask          broadcast   first_host  last_host   total_hosts  usable_hosts", lines[0]);
+        assert_eq!("10.0.0.0/25    255.255.255.128  10.0.0.127  10.0.0.1    10.0.0.126  128          126", lines[1]);
+
+        // Cells holding separators are escaped
+        let allocations = IPNetwork::from_str("10.0.0.0/24").unwrap().vlsm(&[HostRequirement::new("a,\"b\"\tc", 10)]).unwrap();
+        let report = Report::vlsm(&allocations);
+        assert!(report.render(Format::Csv).contains("\n\"a,\"\"b\"\"\tc\",10,"));
+        assert!(report.render(Format::Tsv).contains("\na,\"b\"\\tc\t10\t"));
+        assert!(report.render(Format::Json).contains("\"name\":\"a,\\\"b\\\"\\tc\""));
+
+        assert_eq!(Format::Yaml, Format::from_str("YML").unwrap());
+        assert!(Format::from_str("xml").is_err());
+    }
+
+    #[test]
+    fn special_purpose_blocks() {
+        let block = |address: &str| IPAddress::from_str(address).unwrap().special_purpose().map(|b| b.name.as_str());
+
+        assert_eq!(Some("Private-Use"), block("172.20.1.1"));
+        assert_eq!(Some("Shared Address Space"), block("100.100.0.1"));
+        assert_eq!(Some("Loopback"), block("127.0.0.1"));
+        assert_eq!(Some("Link Local"), block("169.254.10.1"));
+        assert_eq!(Some("Documentation (TEST-NET-2)"), block("198.51.100.7"));
+        assert_eq!(Some("Benchmarking"), block("198.19.255.255"));
+        assert_eq!(Some("Multicast"), block("239.1.2.3"));
+        assert_eq!(Some("Reserved"), block("250.0.0.1"));
+        assert_eq!(Some("Limited Broadcast"), block("255.255.255.255"));
+        assert_eq!(Some("This network"), block("0.1.2.3"));
+        assert_eq!(Some("This host on this network"), block("0.0.0.0"));
+        assert_eq!(Some("NAT64/DNS64 Discovery"), block("192.0.0.171"));
+        assert_eq!(None, block("8.8.8.8"));
+
+        // The most specific block wins, along with its flags
+        let anycast = IPAddress::from_str("192.0.0.9").unwrap();
+        assert_eq!(Some(true), anycast.special_purpose().unwrap().globally_reachable);
+        assert!(anycast.is_global());
+        assert!(!IPAddress::from_str("192.0.0.1").unwrap().is_global());
+        assert!(IPAddress::from_str("1.1.1.1").unwrap().is_global());
+
+        // The registry leaves multicast reachability undefined
+        assert!(!IPAddress::from_str("233.252.0.1").unwrap().is_global());
+
+        let loopback = IPAddress::from_str("127.0.0.1").unwrap().special_purpose().unwrap();
+        assert_eq!((Some(false), Some(false), Some(true)), (loopback.forwardable, loopback.globally_reachable, loopback.reserved_by_protocol));
+        let broadcast = IPAddress::new(255, 255, 255, 255).special_purpose().unwrap();
+        assert_eq!("[RFC8190] [RFC919], Section 7", broadcast.rfc);
+        let deprecated = IPAddress::from_str("192.88.99.1").unwrap().special_purpose().unwrap();
+        assert_eq!(None, deprecated.forwardable);
+
+        assert!(IPAddress::from_str("10.1.2.3").unwrap().is_private());
+        assert!(!IPAddress::from_str("172.32.0.1").unwrap().is_private());
+        assert!(IPAddress::from_str("100.127.255.255").unwrap().is_shared());
+        assert!(IPAddress::from_str("203.0.113.1").unwrap().is_documentation());
+        assert!(IPAddress::from_str("224.0.0.1").unwrap().is_multicast());
+        assert!(IPAddress::new(255, 255, 255, 255).is_reserved());
+        assert!(IPAddress::new(255, 255, 255, 255).is_broadcast());
+
+        // Updated registries may be loaded at runtime
+        let registry = SpecialRegistry::from_csv("Address Block,Name,RFC,Allocation Date,Termination Date,Source,Destination,Forwardable,Globally Reachable,Reserved-by-Protocol\r\n\
+            8.8.8.0/24 [9],\"Example, \"\"quoted\"\" block\",[RFC0000],2030-01,N/A,True,True,True,True [1],False\r\n").unwrap();
+        let example = registry.lookup(&IPAddress::from_str("8.8.8.8").unwrap()).unwrap();
+        assert_eq!("Example, \"quoted\" block", example.name);
+        assert_eq!(Some(true), example.globally_reachable);
+        assert_eq!(2, registry.blocks().len());
+        assert!(registry.lookup(&IPAddress::from_str("10.0.0.1").unwrap()).is_none());
+
+        let error = SpecialRegistry::from_csv("header\n10.0.0.0/8,Private,[RFC1918],1996-02,N/A,True,Maybe,True,False,False\n");
+        assert_eq!("Malformed registry at line 2: invalid flag 'Maybe'", error.err().unwrap().to_string());
+        assert!(SpecialRegistry::from_csv("header\n10.0.0.0/33,A,B,C,D,True,True,True,True,True").is_err());
+        assert!(SpecialRegistry::load("/nonexistent/registry.csv").is_err());
+    }
+
+    #[test]
+    fn classful_analysis() {
+        let class = |address: &str| IPAddress::from_str(address).unwrap().class();
+        assert_eq!(AddressClass::A, class("10.1.2.3"));
+        assert_eq!(AddressClass::A, class("127.255.255.255"));
+        assert_eq!(AddressClass::B, class("128.0.0.0"));
+        assert_eq!(AddressClass::C, class("223.255.255.255"));
+        assert_eq!(AddressClass::D, class("224.0.0.1"));
+        assert_eq!(AddressClass::E, class("240.0.0.1"));
+        assert_eq!("C", AddressClass::C.to_string());
+
+        assert_eq!("255.255.0.0", IPAddress::from_str("172.16.0.1").unwrap().classful_mask().unwrap().to_string());
+        assert!(IPAddress::from_str("239.1.1.1").unwrap().classful_mask().is_none());
+
+        let analysis = IPNetwork::from_str("192.168.1.64/26").unwrap().classful();
+        assert_eq!(24, analysis.default_prefix.unwrap().get());
+        assert_eq!("255.255.255.0", analysis.default_mask.unwrap().to_string());
+        assert_eq!(Some(ClassfulRelation::Subnetted { borrowed_bits: 2 }), analysis.relation);
+        assert_eq!(2, analysis.borrowed_bits());
+
+        let analysis = IPNetwork::from_str("172.16.0.0/12").unwrap().classful();
+        assert_eq!(Some(ClassfulRelation::Supernetted { bits: 4 }), analysis.relation);
+        assert_eq!(0, analysis.borrowed_bits());
+        assert_eq!(Some(ClassfulRelation::Classful), IPNetwork::from_str("10.0.0.0/8").unwrap().classful().relation);
+        assert_eq!(None, IPNetwork::from_str("224.0.0.0/4").unwrap().classful().relation);
+    }
+
+    #[test]
+    fn reverse_dns() {
+        let network = |value: &str| IPNetwork::from_str(value).unwrap();
+
+        assert_eq!("2.1.168.192.in-addr.arpa", IPAddress::from_str("192.168.1.2").unwrap().reverse_name());
+        assert_eq!(vec!["10.in-addr.arpa"], network("10.1.2.3/8").reverse_zones());
+        assert_eq!(vec!["1.168.192.in-addr.arpa"], network("192.168.1.0/24").reverse_zones());
+        assert_eq!(vec!["in-addr.arpa"], network("0.0.0.0/0").reverse_zones());
+
+        let zones = network("172.16.32.0/20").reverse_zones();
+        assert_eq!(16, zones.len());
+        assert_eq!("32.16.172.in-addr.arpa", zones[0]);
+        assert_eq!("47.16.172.in-addr.arpa", zones[15]);
+
+        // RFC 2317 classless delegation
+        assert_eq!(vec!["64/26.1.168.192.in-addr.arpa"], network("192.168.1.70/26").reverse_zones());
+        assert!(network("192.168.1.0/24").classless_delegation().is_none());
+        let delegation = network("192.168.1.70/26").classless_delegation().unwrap();
+        assert_eq!("1.168.192.in-addr.arpa", delegation.parent_zone);
+        assert_eq!(62, delegation.cnames.len());
+        assert_eq!(("65".to_string(), "65.64/26.1.168.192.in-addr.arpa.".to_string()), delegation.cnames[0]);
+
+        let bind = delegation.to_bind(&["ns1.example.net", "ns2.example.net."]);
+        assert!(bind.starts_with("$ORIGIN 1.168.192.in-addr.arpa.\n\
+            64/26\tIN\tNS\tns1.example.net.\n\
+            64/26\tIN\tNS\tns2.example.net.\n\
+            65\tIN\tCNAME\t65.64/26.1.168.192.in-addr.arpa.\n"));
+
+        // PTR records, one $ORIGIN per zone
+        let template = NameTemplate::from_str("host-{b2}-{b3}.example.net").unwrap();
+        assert_eq!("$ORIGIN 64/26.1.168.192.in-addr.arpa.\n65\tIN\tPTR\thost-1-65.example.net.\n", network("192.168.1.64/26").ptr_records(&template).lines().take(2).map(|l| format!("{}\n", l)).collect::<String>());
+
+        let records = network("10.0.0.0/23").ptr_records(&NameTemplate::from_str("{ip}.example.net.").unwrap());
+        let lines: Vec<&str> = records.lines().collect();
+        assert_eq!(512, lines.len());
+        assert_eq!("$ORIGIN 0.0.10.in-addr.arpa.", lines[0]);
+        assert_eq!("1\tIN\tPTR\t10-0-0-1.example.net.", lines[1]);
+        assert_eq!("$ORIGIN 1.0.10.in-addr.arpa.", lines[256]);
+        assert_eq!("254\tIN\tPTR\t10-0-1-254.example.net.", lines[511]);
+
+        let records = network("10.0.0.0/16").ptr_records(&template);
+        assert!(records.starts_with("$ORIGIN 0.10.in-addr.arpa.\n1.0\tIN\tPTR\thost-0-1.example.net.\n"));
+
+        let lines: Vec<String> = network("10.0.0.0/25").ptr_records(&template).lines().map(String::from).collect();
+        assert_eq!("$ORIGIN 0/25.0.0.10.in-addr.arpa.", lines[0]);
+
+        assert_eq!("host-{b2}-{b3}.example.net", template.to_string());
+        assert_eq!(TemplateError::UnknownPlaceholder { name: "b4".to_string(), offset: 5 }.to_string(), NameTemplate::from_str("host-{b4}").unwrap_err().to_string());
+        assert!(matches!(NameTemplate::from_str("host-{b3"), Err(TemplateError::UnclosedPlaceholder { offset: 5 })));
+    }
+
+    #[test]
+    fn forward_dns() {
+        let template = NameTemplate::from_str("host-{b2}-{b3}.example.net").unwrap();
+        let network = IPNetwork::from_str("192.168.1.0/29").unwrap();
+
+        let records: Vec<_> = network.a_records(&template, &ForwardOptions::default()).collect();
+        assert_eq!(6, records.len());
+        assert_eq!("host-1-1.example.net.", records[0].name);
+        assert_eq!(("A", "192.168.1.6"), (records[5].record_type, records[5].value.as_str()));
+
+        // Gateway and excluded addresses are skipped
+        let mut options = ForwardOptions { skip_gateway: true, ..ForwardOptions::default() };
+        options.exclude.insert(&IPNetwork::from(IPAddress::from_str("192.168.1.4").unwrap()));
+        let records: Vec<_> = network.a_records(&template, &options).collect();
+        assert_eq!(vec!["192.168.1.2", "192.168.1.3", "192.168.1.5", "192.168.1.6"], records.iter().map(|r| r.value.as_str()).collect::<Vec<_>>());
+        assert!(to_bind(&records).starts_with("host-1-2.example.net.\tIN\tA\t192.168.1.2\nhost-1-3.example.net.\tIN\tA\t192.168.1.3\n"));
+
+        // AAAA records, skipping the subnet-router anycast address
+        let v6 = IPv6Network::from_str("2001:db8::/64").unwrap();
+        let template_v6 = NameTemplate::from_str("v6-{s7}.example.net.").unwrap();
+        let mut options = ForwardOptions::default();
+        options.exclude_v6.insert(IPv6Address::from_str("2001:db8::2").unwrap());
+        let records: Vec<_> = v6.aaaa_records(&template_v6, &options).take(3).collect();
+        assert_eq!(("v6-1.example.net.", "AAAA", "2001:db8::1"), (records[0].name.as_str(), records[0].record_type, records[0].value.as_str()));
+        assert_eq!("2001:db8::3", records[1].value);
+        assert_eq!("v6-a.example.net.", template_v6.render_v6(&IPv6Address::from_str("2001:db8::a").unwrap()));
+        assert_eq!(2, IPv6Network::from_str("2001:db8::/127").unwrap().hosts().count());
+        assert_eq!("ffff", NameTemplate::from_str("{s5}").unwrap().render(&IPAddress::new(10, 0, 0, 1)).trim_end_matches('.'));
+
+        // octodns zone, names relative to the zone
+        let mut records: Vec<_> = network.a_records(&template, &ForwardOptions::default()).take(2).collect();
+        records.push(records[0].clone());
+        records[2].value = "192.168.1.9".to_string();
+        records.extend(v6.aaaa_records(&NameTemplate::from_str("host-1-1.example.net").unwrap(), &ForwardOptions::default()).take(1));
+        assert_eq!("{\"host-1-1\":[{\"type\":\"A\",\"values\":[\"192.168.1.1\",\"192.168.1.9\"]},{\"type\":\"AAAA\",\"value\":\"2001:db8::1\"}],\
+            \"host-1-2\":{\"type\":\"A\",\"value\":\"192.168.1.2\"}}\n", to_octodns(&records, "example.net.").unwrap());
+        assert!(matches!(to_octodns(&records, "example.org"), Err(TemplateError::OutsideZone { .. })));
+    }
+
+    #[test]
+    fn ipam_allocations() {
+        let network = |value: &str| IPNetwork::from_str(value).unwrap();
+        let address = |value: &str| IPAddress::from_str(value).unwrap();
+        let mut ipam = Ipam::new();
+
+        ipam.add_pool(&network("10.0.0.0/16"), Metadata::new("netops", "campus", &["prod"])).unwrap();
+        assert!(matches!(ipam.add_pool(&network("10.0.128.0/17"), Metadata::default()), Err(IpamError::Overlap { .. })));
+        assert!(matches!(ipam.add_pool(&network("10.0.0.0/8"), Metadata::default()), Err(IpamError::Overlap { .. })));
+
+        // Next free subnets fill the gaps left by explicit reservations
+        let pool = network("10.0.0.0/16");
+        ipam.reserve_subnet(&network("10.0.1.0/24"), Metadata::new("voice", "", &[])).unwrap();
+        assert_eq!(network("10.0.0.0/24"), ipam.allocate_subnet(&pool, 24, Metadata::default()).unwrap());
+        assert_eq!(network("10.0.2.0/23"), ipam.allocate_subnet(&pool, 23, Metadata::default()).unwrap());
+        assert_eq!(network("10.0.4.0/26"), ipam.allocate_subnet(&pool, 26, Metadata::default()).unwrap());
+        assert_eq!(network("10.0.5.0/24"), ipam.allocate_subnet(&pool, 24, Metadata::default()).unwrap());
+        assert!(matches!(ipam.reserve_subnet(&network("10.0.2.128/25"), Metadata::default()), Err(IpamError::Overlap { .. })));
+        assert!(matches!(ipam.reserve_subnet(&network("192.168.0.0/24"), Metadata::default()), Err(IpamError::OutsideParent { .. })));
+        assert!(matches!(ipam.allocate_subnet(&pool, 8, Metadata::default()), Err(IpamError::Netmask { .. })));
+        assert!(matches!(ipam.allocate_subnet(&pool, 15, Metadata::default()), Err(IpamError::Netmask { .. })));
+        assert!(matches!(ipam.allocate_subnet(&pool, 16, Metadata::default()), Err(IpamError::Exhausted { .. })));
+        assert_eq!(5, ipam.pool(&pool).unwrap().subnets.len());
+
+        // Next free hosts skip reservations
+        let subnet = network("10.0.4.0/26");
+        ipam.reserve_host(&address("10.0.4.2"), Metadata::new("", "gateway", &[])).unwrap();
+        assert_eq!(address("10.0.4.1"), ipam.allocate_host(&subnet, Metadata::default()).unwrap());
+        assert_eq!(address("10.0.4.3"), ipam.allocate_host(&subnet, Metadata::default()).unwrap());
+        assert!(matches!(ipam.reserve_host(&address("10.0.4.3"), Metadata::default()), Err(IpamError::Duplicate { .. })));
+        assert!(matches!(ipam.reserve_host(&address("10.0.4.63"), Metadata::default()), Err(IpamError::OutsideParent { .. })));
+        assert_eq!("gateway", ipam.release_host(&address("10.0.4.2")).unwrap().metadata.description);
+        assert_eq!(address("10.0.4.2"), ipam.allocate_host(&subnet, Metadata::default()).unwrap());
+        assert!(matches!(ipam.release_host(&address("10.0.4.9")), Err(IpamError::NotFound { .. })));
+
+        let small = ipam.allocate_subnet(&pool, 31, Metadata::default()).unwrap();
+        assert_eq!(network("10.0.4.64/31"), small);
+        ipam.allocate_host(&small, Metadata::default()).unwrap();
+        ipam.allocate_host(&small, Metadata::default()).unwrap();
+        assert!(matches!(ipam.allocate_host(&small, Metadata::default()), Err(IpamError::Exhausted { .. })));
+
+        // Releasing frees the space again
+        assert!(matches!(ipam.remove_pool(&pool), Err(IpamError::InUse { .. })));
+        assert_eq!(3, ipam.release_subnet(&subnet).unwrap().hosts.len());
+        assert_eq!(subnet, ipam.allocate_subnet(&pool, 26, Metadata::default()).unwrap());
+        assert!(ipam.subnet(&subnet).unwrap().hosts.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "persist")]
+    fn ipam_persistence() {
+        let network = |value: &str| IPNetwork::from_str(value).unwrap();
+        let mut ipam = Ipam::new();
+        ipam.add_pool(&network("10.0.0.0/16"), Metadata::new("netops", "campus", &["prod", "site-a"])).unwrap();
+        let subnet = ipam.allocate_subnet(&network("10.0.0.0/16"), 24, Metadata::new("sales", "office LAN", &["vlan-20"])).unwrap();
+        ipam.allocate_host(&subnet, Metadata::new("sales", "printer", &[])).unwrap();
+
+        let directory = std::env::temp_dir().join(format!("subnet-ipam-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        for file in ["ipam.json", "ipam.toml"] {
+            let path = directory.join(file);
+            ipam.save(&path).unwrap();
+            assert_eq!(ipam, Ipam::load(&path).unwrap());
+            // Saving again replaces the file
+            ipam.save(&path).unwrap();
+        }
+
+        let json = std::fs::read_to_string(directory.join("ipam.json")).unwrap();
+        assert!(json.contains("\"network\": \"10.0.0.0/24\""));
+        assert!(json.contains("\"description\": \"printer\""));
+
+        // Overlaps written by hand are refused on load
+        let path = directory.join("overlap.json");
+        let mut overlapping = ipam.clone();
+        overlapping.reserve_subnet(&network("10.0.1.0/25"), Metadata::default()).unwrap();
+        overlapping.save(&path).unwrap();
+        let edited = std::fs::read_to_string(&path).unwrap().replace("10.0.1.0/25", "10.0.0.128/25");
+        std::fs::write(&path, edited).unwrap();
+        assert!(matches!(Ipam::load(&path), Err(IpamError::Overlap { .. })));
+        std::fs::write(&path, json.replace("10.0.0.0/24", "10.0.0.0/8")).unwrap();
+        assert!(matches!(Ipam::load(&path), Err(IpamError::OutsideParent { .. })));
+        std::fs::write(&path, "{\"version\": 2}").unwrap();
+        assert!(matches!(Ipam::load(&path), Err(IpamError::Format { .. })));
+        assert!(matches!(Ipam::load(directory.join("missing.json")), Err(IpamError::Io { .. })));
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn address_plan_lint() {
+        let entry = |name: &str, network: &str| PlanEntry::new(name, IPNetwork::from_str(network).unwrap());
+        let plan = vec![
+            entry("vpc-prod", "10.0.0.0/16"),
+            entry("vlan-10", "10.0.1.0/24"),
+            entry("vpn", "192.168.0.0/24"),
+            entry("vlan-10-copy", "10.0.1.0/24"),
+            entry("srv1", "10.0.1.5/24"),
+            entry("srv2", "10.0.1.6/25"),
+            entry("srv3", "10.0.1.5/24"),
+            entry("typo", "172.16.0.5/24"),
+            entry("lab", "192.168.1.0/24"),
+        ];
+
+        let diagnostics = lint(&plan);
+        let summary: Vec<(Severity, &str, Vec<&str>)> = diagnostics.iter().map(|d| (d.severity, d.code, d.entries.iter().map(|e| e.as_str()).collect())).collect();
+        assert_eq!(vec![
+            (Severity::Error, "duplicate", vec!["vlan-10", "vlan-10-copy"]),
+            (Severity::Error, "duplicate", vec!["srv1", "srv3"]),
+            (Severity::Error, "cidr-mismatch", vec!["srv2", "vlan-10"]),
+            (Severity::Warning, "overlap", vec!["vpc-prod", "vlan-10"]),
+            (Severity::Warning, "misaligned", vec!["typo"]),
+        ], summary);
+
+        assert_eq!("error[cidr-mismatch]: 'srv2' (10.0.1.6/25) has prefix /25, but 'vlan-10' (10.0.1.0/24) holding it has prefix /24", diagnostics[2].to_string());
+        assert_eq!("warning[misaligned]: 'typo' (172.16.0.5/24) has host bits set, the network is 172.16.0.0/24", diagnostics[4].to_string());
+        assert!(Severity::Error > Severity::Warning);
+
+        let csv = Report::diagnostics(&diagnostics).render(Format::Csv);
+        assert!(csv.starts_with("severity,code,entries,message\nerror,duplicate,\"vlan-10,vlan-10-copy\","));
+
+        assert!(lint(&[entry("a", "10.0.0.0/24"), entry("b", "10.0.1.0/24"), entry("gw", "10.0.0.1/24")]).is_empty());
+    }
+
+    #[test]
+    fn cloud_plan() {
+        let vpc = IPNetwork::from_str("10.0.0.0/16").unwrap();
+        let plan = vpc.cloud_plan(Provider::Aws, &["us-east-1a", "us-east-1b", "us-east-1c"], &["public", "private", "db"]).unwrap();
+
+        // 9 subnets need 4 borrowed bits, tiers are contiguous
+        assert_eq!(9, plan.len());
+        assert_eq!(("public", "us-east-1a", "10.0.0.0/20"), (plan[0].tier.as_str(), plan[0].zone.as_str(), plan[0].network.to_string().as_str()));
+        assert_eq!(("private", "us-east-1a", "10.0.48.0/20"), (plan[3].tier.as_str(), plan[3].zone.as_str(), plan[3].network.to_string().as_str()));
+        assert_eq!(("db", "us-east-1c", "10.0.128.0/20"), (plan[8].tier.as_str(), plan[8].zone.as_str(), plan[8].network.to_string().as_str()));
+
+        // AWS keeps the first four and the last address
+        assert_eq!(4096 - 5, plan[0].usable_hosts);
+        assert_eq!("10.0.0.4", plan[0].first_usable.to_string());
+        assert_eq!("10.0.15.254", plan[0].last_usable.to_string());
+        assert_eq!(vec!["10.0.0.0", "10.0.0.1", "10.0.0.2", "10.0.0.3", "10.0.15.255"], plan[0].reserved.iter().map(|a| a.to_string()).collect::<Vec<_>>());
+
+        // GCP keeps the two first and two last addresses
+        let plan = IPNetwork::from_str("10.1.0.0/24").unwrap().cloud_plan(Provider::Gcp, &["a", "b"], &["app"]).unwrap();
+        assert_eq!(128 - 4, plan[1].usable_hosts);
+        assert_eq!(("10.1.0.130", "10.1.0.253"), (plan[1].first_usable.to_string().as_str(), plan[1].last_usable.to_string().as_str()));
+        assert_eq!(vec!["10.1.0.128", "10.1.0.129", "10.1.0.254", "10.1.0.255"], plan[1].reserved.iter().map(|a| a.to_string()).collect::<Vec<_>>());
+        let plan = IPNetwork::from_str("10.1.0.0/24").unwrap().cloud_plan(Provider::Azure, &["1"], &["web"]).unwrap();
+        assert_eq!(256 - 5, plan[0].usable_hosts);
+
+        // Disallowed prefixes are rejected
+        let error = IPNetwork::from_str("10.0.0.0/27").unwrap().cloud_plan(Provider::Aws, &["a", "b", "c"], &["public"]).unwrap_err();
+        assert_eq!("aws does not allow /29 networks, only /16 to /28", error.to_string());
+        assert!(matches!(IPNetwork::from_str("10.0.0.0/8").unwrap().cloud_plan(Provider::Aws, &["a"], &["public"]), Err(CloudError::PrefixNotAllowed { cidr: 8, .. })));
+        assert!(matches!(vpc.cloud_plan(Provider::Aws, &[], &["public"]), Err(CloudError::NoZones)));
+        assert!(matches!(vpc.cloud_plan(Provider::Aws, &["a"], &[]), Err(CloudError::NoTiers)));
+        assert_eq!(Provider::Gcp, Provider::from_str("GCP").unwrap());
+        assert!(matches!(Provider::from_str("oci"), Err(CloudError::UnknownProvider { .. })));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_human_readable() {
+        let network = IPNetwork::from_str("10.0.0.0/8").unwrap();
+        assert_eq!("\"10.0.0.0/8\"", serde_json::to_string(&network).unwrap());
+        assert_eq!(network, serde_json::from_str::<IPNetwork>("\"10.0.0.0/8\"").unwrap());
+
+        let range = IPRange::from_str("10.0.0.5-10.0.3.200").unwrap();
+        assert_eq!("\"10.0.0.5-10.0.3.200\"", serde_json::to_string(&range).unwrap());
+        let ipv6 = IPv6Network::from_str("2001:db8::/32").unwrap();
+        assert_eq!(ipv6, serde_json::from_str::<IPv6Network>(&serde_json::to_string(&ipv6).unwrap()).unwrap());
+
+        // Deserialization goes through the strict parser
+        assert!(serde_json::from_str::<IPNetwork>("\"10.0.0.0/33\"").is_err());
+        assert!(serde_json::from_str::<IPAddress>("\"010.0.0.1\"").is_err());
+        assert!(serde_json::from_str::<IPAddress>("\"10.0.0.1/8\"").is_err());
+        assert!(serde_json::from_str::<PrefixLen>("33").is_err());
+
+        let set = IpSet::from_cidrs(&[network, IPNetwork::from_str("192.168.0.0/16").unwrap()]);
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!("[\"10.0.0.0-10.255.255.255\",\"192.168.0.0-192.168.255.255\"]", json);
+        assert_eq!(set, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_binary() {
+        let network = IPNetwork::from_str("192.168.1.0/24").unwrap();
+        let bytes = bincode::serialize(&network).unwrap();
+        assert_eq!(vec![192, 168, 1, 0, 24], bytes);
+        assert_eq!(network, bincode::deserialize::<IPNetwork>(&bytes).unwrap());
+
+        let ipv6 = IPv6Network::from_str("2001:db8::1/64").unwrap();
+        let bytes = bincode::serialize(&ipv6).unwrap();
+        assert_eq!(17, bytes.len());
+        assert_eq!(ipv6, bincode::deserialize::<IPv6Network>(&bytes).unwrap());
+
+        assert_eq!(4, bincode::serialize(&SubnetMask::from_cidr(8).unwrap()).unwrap().len());
+        assert!(bincode::deserialize::<IPNetwork>(&[10, 0, 0, 0, 33]).is_err());
+        assert!(bincode::deserialize::<IPRange>(&[10, 0, 0, 2, 10, 0, 0, 1]).is_err());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::fmt;
+use crate::network::IPNetwork;
+use crate::trie::PrefixTrie;
+
+/// Severity of a diagnostic, from the least to the most severe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Likely a mistake, e.g. a network nested in another one
+    Warning,
+    /// Certainly a conflict, e.g. the same network declared twice
+    Error,
+}
+
+/// Named network of an address plan, like a VPC, a VLAN or a VPN range.
+/// Entries with host bits set, like `10.0.1.5/24`, describe a host along with
+/// the prefix length of its subnet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlanEntry {
+    /// Name of the entry, e.g. `vpc-prod`
+    pub name: String,
+    /// Declared network
+    pub network: IPNetwork,
+}
+
+/// Finding of the address plan linter
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Severity of the finding
+    pub severity: Severity,
+    /// Stable identifier of the check: `duplicate`, `overlap`,
+    /// `cidr-mismatch` or `misaligned`
+    pub code: &'static str,
+    /// Names of the entries involved
+    pub entries: Vec<String>,
+    /// Human readable description
+    pub message: String,
+}
+
+impl fmt::Display for Severity {
+    /// Formats a severity as a lower case word
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl PlanEntry {
+    /// Creates a new address plan entry
+    /// 
+    /// Parameters:
+    /// * `name`: name of the entry
+    /// * `network`: declared network
+    pub fn new(name: &str, network: IPNetwork) -> PlanEntry {
+        PlanEntry { name: name.to_string(), network }
+    }
+
+    /// Checks whether this entry describes a host, i.e. its address has host bits set
+    fn is_host(&self) -> bool {
+        self.network.calculate_subnet() != self.network
+    }
+
+    /// Describes this entry in messages, e.g. `'lan' (10.0.1.0/24)`
+    fn describe(&self) -> String {
+        format!("'{}' ({})", self.name, self.network)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    /// Formats a diagnostic as a single line, e.g. `error[duplicate]: ...`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
+
+/// Checks an address plan for conflicts:
+/// * `duplicate` (error): two networks, or two hosts, with the same address
+/// * `overlap` (warning): a network nested in another one
+/// * `cidr-mismatch` (error): a host whose prefix length differs from the one
+///   of the most specific network holding it
+/// * `misaligned` (warning): a host held by no network, likely a network
+///   written with host bits set, like `10.0.0.5/24`
+/// 
+/// Diagnostics are sorted by decreasing severity, then by entry order.
+/// 
+/// Parameters:
+/// * `entries`: entries of the address plan
+pub fn lint(entries: &[PlanEntry]) -> Vec<Diagnostic> {
+    let mut found: Vec<(usize, Diagnostic)> = Vec::new();
+    let mut networks: Vec<usize> = Vec::new();
+    let mut hosts: Vec<usize> = Vec::new();
+
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.is_host() {
+            hosts.push(i);
+        } else {
+            networks.push(i);
+        }
+    }
+
+    // Duplicates, among networks and among hosts
+    for group in [&networks, &hosts] {
+        let mut seen: HashMap<IPNetwork, usize> = HashMap::new();
+        for &i in group {
+            let key = if entries[i].is_host() { IPNetwork::from(entries[i].network.address) } else { entries[i].network };
+            match seen.get(&key) {
+                Some(&first) => found.push((first, diagnostic(Severity::Error, "duplicate", &[&entries[first], &entries[i]],
+                    format!("{} and {} use the same address", entries[first].describe(), entries[i].describe())))),
+                None => {
+                    seen.insert(key, i);
+                }
+            }
+        }
+    }
+
+    // Nested networks: sweep by start address, keeping the chain of
+    // networks still open at that point
+    let mut sorted: Vec<usize> = networks.clone();
+    sorted.sort_by_key(|&i| (entries[i].network.bounds().0, entries[i].network.cidr(), i));
+    let mut open: Vec<usize> = Vec::new();
+
+    for &i in &sorted {
+        let (start, _) = entries[i].network.bounds();
+        while open.last().is_some_and(|&o| entries[o].network.bounds().1 < start) {
+            open.pop();
+        }
+
+        if let Some(&outer) = open.last() {
+            if entries[outer].network != entries[i].network {
+                found.push((outer.min(i), diagnostic(Severity::Warning, "overlap", &[&entries[outer], &entries[i]],
+                    format!("{} is nested in {}", entries[i].describe(), entries[outer].describe()))));
+            }
+        }
+
+        open.push(i);
+    }
+
+    // Hosts against the most specific network holding them
+    let mut trie = PrefixTrie::new();
+    for &i in &networks {
+        if trie.get(&entries[i].network).is_none() {
+            trie.insert(&entries[i].network, i);
+        }
+    }
+
+    for &i in &hosts {
+        let host = &entries[i];
+        let subnet = host.network.calculate_subnet();
+
+        match trie.longest_match(&host.network.address) {
+            Some((network, _)) if network.prefix == host.network.prefix => {}
+            Some((_, &n)) => found.push((i, diagnostic(Severity::Error, "cidr-mismatch", &[host, &entries[n]],
+                format!("{} has prefix /{}, but {} holding it has prefix /{}", host.describe(), host.network.cidr(), entries[n].describe(), entries[n].network.cidr())))),
+            None => found.push((i, diagnostic(Severity::Warning, "misaligned", &[host],
+                format!("{} has host bits set, the network is {}", host.describe(), subnet)))),
+        }
+    }
+
+    found.sort_by_key(|(i, diagnostic)| (std::cmp::Reverse(diagnostic.severity), *i));
+    found.into_iter().map(|(_, diagnostic)| diagnostic).collect()
+}
+
+/// Creates a diagnostic about some entries
+fn diagnostic(severity: Severity, code: &'static str, entries: &[&PlanEntry], message: String) -> Diagnostic {
+    Diagnostic { severity, code, entries: entries.iter().map(|entry| entry.name.clone()).collect(), message }
+}
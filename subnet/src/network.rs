@@ -0,0 +1,294 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::constants::{MAX_CIDR, MAX_CIDR_V6};
+use crate::parse::{parse_prefix, ParseOptions};
+use crate::types::{IPAddress, IPv6Address, IPv6SubnetMask, NetmaskError, ParseError, SubnetMask, WildcardMask};
+
+/// Prefix length (CIDR value) of an IPv4 network, always between 0 and 32
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PrefixLen(u8);
+
+/// Prefix length (CIDR value) of an IPv6 network, always between 0 and 128
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IPv6PrefixLen(u8);
+
+/// Represents an IPv4 network: an address along with a valid prefix length.
+/// The address may have host bits set, e.g. `192.168.1.2/24`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IPNetwork {
+    /// Address of the network
+    pub address: IPAddress,
+    /// Prefix length of the network
+    pub prefix: PrefixLen,
+}
+
+/// Represents an IPv6 network: an address along with a valid prefix length
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IPv6Network {
+    /// Address of the network
+    pub address: IPv6Address,
+    /// Prefix length of the network
+    pub prefix: IPv6PrefixLen,
+}
+
+impl PrefixLen {
+    /// Longest prefix length, a single host
+    pub const MAX: PrefixLen = PrefixLen(MAX_CIDR);
+
+    /// Creates a new prefix length
+    /// 
+    /// Parameters:
+    /// * `cidr`: CIDR decimal value, from 0 to 32
+    pub fn new(cidr: u8) -> Result<PrefixLen, NetmaskError> {
+        if cidr > MAX_CIDR {
+            return Err(NetmaskError::MaxCidrExceeded { value: cidr });
+        }
+
+        Ok(PrefixLen(cidr))
+    }
+
+    /// Creates a prefix length from a value already known to be valid
+    pub(crate) fn clamped(cidr: u8) -> PrefixLen {
+        PrefixLen(cidr.min(MAX_CIDR))
+    }
+
+    /// Returns the CIDR decimal value
+    pub fn get(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns the subnet mask of this prefix length
+    pub fn netmask(&self) -> SubnetMask {
+        SubnetMask::from_prefix(*self)
+    }
+
+    /// Returns the wildcard (inverse) mask of this prefix length
+    pub fn wildcard(&self) -> WildcardMask {
+        self.netmask().to_wildcard()
+    }
+}
+
+impl TryFrom<u8> for PrefixLen {
+    type Error = NetmaskError;
+
+    fn try_from(cidr: u8) -> Result<PrefixLen, NetmaskError> {
+        PrefixLen::new(cidr)
+    }
+}
+
+impl From<PrefixLen> for u8 {
+    fn from(prefix: PrefixLen) -> u8 {
+        prefix.0
+    }
+}
+
+impl fmt::Display for PrefixLen {
+    /// Formats a prefix length as its CIDR decimal value
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl IPv6PrefixLen {
+    /// Longest prefix length, a single host
+    pub const MAX: IPv6PrefixLen = IPv6PrefixLen(MAX_CIDR_V6);
+
+    /// Creates a new IPv6 prefix length
+    /// 
+    /// Parameters:
+    /// * `cidr`: CIDR decimal value, from 0 to 128
+    pub fn new(cidr: u8) -> Result<IPv6PrefixLen, NetmaskError> {
+        if cidr > MAX_CIDR_V6 {
+            return Err(NetmaskError::MaxCidrExceeded { value: cidr });
+        }
+
+        Ok(IPv6PrefixLen(cidr))
+    }
+
+    /// Returns the CIDR decimal value
+    pub fn get(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns the subnet mask of this prefix length
+    pub fn netmask(&self) -> IPv6SubnetMask {
+        IPv6SubnetMask::from_prefix(*self)
+    }
+}
+
+impl TryFrom<u8> for IPv6PrefixLen {
+    type Error = NetmaskError;
+
+    fn try_from(cidr: u8) -> Result<IPv6PrefixLen, NetmaskError> {
+        IPv6PrefixLen::new(cidr)
+    }
+}
+
+impl From<IPv6PrefixLen> for u8 {
+    fn from(prefix: IPv6PrefixLen) -> u8 {
+        prefix.0
+    }
+}
+
+impl fmt::Display for IPv6PrefixLen {
+    /// Formats a prefix length as its CIDR decimal value
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl IPNetwork {
+    /// Creates a new IPv4 network
+    /// 
+    /// Parameters:
+    /// * `address`: address of the network, host bits may be set
+    /// * `prefix`: prefix length of the network
+    pub fn new(address: IPAddress, prefix: PrefixLen) -> IPNetwork {
+        IPNetwork { address, prefix }
+    }
+
+    /// Creates a new IPv4 network from a raw CIDR value
+    /// 
+    /// Parameters:
+    /// * `address`: address of the network, host bits may be set
+    /// * `cidr`: CIDR decimal value, from 0 to 32
+    pub fn with_cidr(address: IPAddress, cidr: u8) -> Result<IPNetwork, NetmaskError> {
+        Ok(IPNetwork::new(address, PrefixLen::new(cidr)?))
+    }
+
+    /// Constructs a network from the 32 bit numeric value of its address
+    /// 
+    /// Parameters:
+    /// * `value`: numeric value of the address
+    /// * `prefix`: prefix length of the network
+    pub fn from_u32(value: u32, prefix: PrefixLen) -> IPNetwork {
+        IPNetwork::new(IPAddress::from_u32(value), prefix)
+    }
+
+    /// Construct a network from string parameter, like `192.168.1.2/24`.
+    /// The CIDR value is required.
+    /// 
+    /// Parameters:
+    /// * `network`: String value with network
+    pub fn from_string(network: String) -> Result<IPNetwork, ParseError> {
+        IPNetwork::from_str_with_options(&network, &ParseOptions::new())
+    }
+
+    /// Constructs a network from string slice with custom parse options
+    /// 
+    /// Parameters:
+    /// * `network`: string slice value with network
+    /// * `options`: parse options
+    pub fn from_str_with_options(network: &str, options: &ParseOptions) -> Result<IPNetwork, ParseError> {
+        let (address, cidr) = parse_prefix(network, MAX_CIDR, options)?;
+        Ok(IPNetwork::new(IPAddress::from_str_with_options(address, options)?, PrefixLen(cidr)))
+    }
+
+    /// Returns the CIDR decimal value of this network
+    pub fn cidr(&self) -> u8 {
+        self.prefix.get()
+    }
+
+    /// Returns the subnet mask of this network
+    pub fn netmask(&self) -> SubnetMask {
+        self.prefix.netmask()
+    }
+
+    /// Calculates the subnet associated with this network, i.e. the same
+    /// network with every host bit cleared
+    pub fn calculate_subnet(&self) -> IPNetwork {
+        let netmask = self.netmask();
+        let mut result = *self;
+
+        result.address.b0 &= netmask.b0;
+        result.address.b1 &= netmask.b1;
+        result.address.b2 &= netmask.b2;
+        result.address.b3 &= netmask.b3;
+
+        result
+    }
+}
+
+impl FromStr for IPNetwork {
+    type Err = ParseError;
+
+    /// Constructs a network from string slice, like `192.168.1.2/24`
+    fn from_str(network: &str) -> Result<IPNetwork, ParseError> {
+        IPNetwork::from_str_with_options(network, &ParseOptions::new())
+    }
+}
+
+impl fmt::Display for IPNetwork {
+    /// Formats a network as a standard string (dot.decimal + CIDR)
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+impl IPv6Network {
+    /// Creates a new IPv6 network
+    /// 
+    /// Parameters:
+    /// * `address`: address of the network, host bits may be set
+    /// * `prefix`: prefix length of the network
+    pub fn new(address: IPv6Address, prefix: IPv6PrefixLen) -> IPv6Network {
+        IPv6Network { address, prefix }
+    }
+
+    /// Creates a new IPv6 network from a raw CIDR value
+    /// 
+    /// Parameters:
+    /// * `address`: address of the network, host bits may be set
+    /// * `cidr`: CIDR decimal value, from 0 to 128
+    pub fn with_cidr(address: IPv6Address, cidr: u8) -> Result<IPv6Network, NetmaskError> {
+        Ok(IPv6Network::new(address, IPv6PrefixLen::new(cidr)?))
+    }
+
+    /// Construct an IPv6 network from string parameter, like `2001:db8::1/64`.
+    /// The CIDR value is required.
+    /// 
+    /// Parameters:
+    /// * `network`: String value with IPv6 network
+    pub fn from_string(network: String) -> Result<IPv6Network, ParseError> {
+        let (address, cidr) = parse_prefix(&network, MAX_CIDR_V6, &ParseOptions::new())?;
+        Ok(IPv6Network::new(IPv6Address::from_str(address)?, IPv6PrefixLen(cidr)))
+    }
+
+    /// Returns the CIDR decimal value of this network
+    pub fn cidr(&self) -> u8 {
+        self.prefix.get()
+    }
+
+    /// Returns the subnet mask of this network
+    pub fn netmask(&self) -> IPv6SubnetMask {
+        self.prefix.netmask()
+    }
+
+    /// Calculates the subnet associated with this IPv6 network
+    pub fn calculate_subnet(&self) -> IPv6Network {
+        let netmask = self.netmask();
+        let mut result = *self;
+
+        for (segment, mask) in result.address.segments.iter_mut().zip(netmask.segments) {
+            *segment &= mask;
+        }
+
+        result
+    }
+}
+
+impl FromStr for IPv6Network {
+    type Err = ParseError;
+
+    /// Constructs an IPv6 network from string slice, like `2001:db8::1/64`
+    fn from_str(network: &str) -> Result<IPv6Network, ParseError> {
+        IPv6Network::from_string(network.to_string())
+    }
+}
+
+impl fmt::Display for IPv6Network {
+    /// Formats an IPv6 network as its canonical RFC 5952 string + CIDR
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
@@ -0,0 +1,128 @@
+use crate::types::ParseError;
+
+/// Options tuning the strict dot.decimal parser
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ParseOptions {
+    /// Accept decimal components with leading zeros, like `010.0.0.1`
+    pub allow_leading_zeros: bool,
+    /// Accept every BSD/glibc inet_aton form, like `10.1`, `0x0a000001` or
+    /// `012.0.0.1`. Leading zeros then mean octal.
+    pub inet_aton: bool,
+}
+
+impl ParseOptions {
+    /// Creates the default, strict, parse options
+    pub fn new() -> ParseOptions {
+        ParseOptions::default()
+    }
+}
+
+impl ParseError {
+    /// Moves the byte offset of this error by `by` bytes, for errors raised
+    /// while parsing a slice of a larger input
+    pub(crate) fn offset_by(self, by: usize) -> ParseError {
+        match self {
+            ParseError::GenericError { position, value, offset } => ParseError::GenericError { position, value, offset: offset + by },
+            ParseError::MaxCidrExceeded { value, offset } => ParseError::MaxCidrExceeded { value, offset: offset + by },
+            ParseError::EmptyComponent { position, offset } => ParseError::EmptyComponent { position, offset: offset + by },
+            ParseError::MissingComponent { position, offset } => ParseError::MissingComponent { position, offset: offset + by },
+            ParseError::OutOfRange { position, value, offset } => ParseError::OutOfRange { position, value, offset: offset + by },
+            ParseError::LeadingZero { position, value, offset } => ParseError::LeadingZero { position, value, offset: offset + by },
+            ParseError::TrailingGarbage { value, offset } => ParseError::TrailingGarbage { value, offset: offset + by },
+        }
+    }
+}
+
+/// Parses four dot separated decimal bytes. Never panics, every error carries
+/// the byte offset of the offending token.
+/// 
+/// Parameters:
+/// * `input`: string slice to parse
+/// * `options`: parse options
+pub(crate) fn parse_dotted(input: &str, options: &ParseOptions) -> Result<[u8; 4], ParseError> {
+    let mut bytes = [0; 4];
+    let mut offset = 0;
+    let mut count = 0;
+
+    for chunk in input.split('.') {
+        if count == bytes.len() {
+            return Err(ParseError::TrailingGarbage { value: input[offset - 1..].to_string(), offset: offset - 1 });
+        }
+
+        bytes[count] = parse_decimal(chunk, offset, &format!("byte {}", count), 0xFF, options)? as u8;
+        offset += chunk.len() + 1;
+        count += 1;
+    }
+
+    if count < bytes.len() {
+        return Err(ParseError::MissingComponent { position: format!("byte {}", count), offset: input.len() });
+    }
+
+    Ok(bytes)
+}
+
+/// Splits an `address/prefix` string and parses its prefix length, which is
+/// required and may not exceed `max`. Returns the address part, left for the
+/// caller to parse, along with the prefix length.
+/// 
+/// Parameters:
+/// * `input`: string slice to parse
+/// * `max`: maximum accepted prefix length
+/// * `options`: parse options
+pub(crate) fn parse_prefix<'a>(input: &'a str, max: u8, options: &ParseOptions) -> Result<(&'a str, u8), ParseError> {
+    let start = match input.find('/') {
+        Some(i) => i + 1,
+        None => return Err(ParseError::MissingComponent { position: "CIDR value".to_string(), offset: input.len() }),
+    };
+
+    // Only a single CIDR suffix is allowed
+    let text = &input[start..];
+    if let Some(i) = text.find('/') {
+        return Err(ParseError::TrailingGarbage { value: text[i..].to_string(), offset: start + i });
+    }
+
+    let value = parse_decimal(text, start, "CIDR value", 0xFF, options)? as u8;
+    if value > max {
+        return Err(ParseError::MaxCidrExceeded { value, offset: start });
+    }
+
+    Ok((&input[..start - 1], value))
+}
+
+/// Rejects a CIDR suffix on input that describes a bare address or mask
+/// 
+/// Parameters:
+/// * `input`: string slice to check
+pub(crate) fn reject_prefix(input: &str) -> Result<(), ParseError> {
+    match input.find('/') {
+        Some(i) => Err(ParseError::TrailingGarbage { value: input[i..].to_string(), offset: i }),
+        None => Ok(()),
+    }
+}
+
+/// Parses a plain decimal number: ASCII digits only, no sign, no whitespace
+/// 
+/// Parameters:
+/// * `token`: string slice to parse
+/// * `offset`: byte offset of the token in the whole input
+/// * `position`: human readable name of the token
+/// * `max`: maximum accepted value
+/// * `options`: parse options
+pub(crate) fn parse_decimal(token: &str, offset: usize, position: &str, max: u32, options: &ParseOptions) -> Result<u32, ParseError> {
+    if token.is_empty() {
+        return Err(ParseError::EmptyComponent { position: position.to_string(), offset });
+    }
+
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::GenericError { position: position.to_string(), value: token.to_string(), offset });
+    }
+
+    if token.len() > 1 && token.starts_with('0') && !options.allow_leading_zeros {
+        return Err(ParseError::LeadingZero { position: position.to_string(), value: token.to_string(), offset });
+    }
+
+    match token.parse::<u32>() {
+        Ok(value) if value <= max => Ok(value),
+        _ => Err(ParseError::OutOfRange { position: position.to_string(), value: token.to_string(), offset }),
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+use std::str::FromStr;
+use custom_error::custom_error;
+use crate::aggregate::{aggregate, range_to_cidrs};
+use crate::network::IPNetwork;
+use crate::types::{IPAddress, NetmaskError, ParseError};
+
+custom_error!{
+    /// Describes an error related with an IPRange type
+    pub RangeError
+        Reversed{start: String, end: String} = "Range start {start} is after range end {end}",
+        NotContiguous = "Networks do not form a single contiguous range",
+        Parse{source: ParseError} = "{source}",
+        Netmask{source: NetmaskError} = "{source}"
+}
+
+/// Represents an inclusive range of IP addresses
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IPRange {
+    /// First address of the range
+    pub start: IPAddress,
+    /// Last address of the range
+    pub end: IPAddress,
+}
+
+impl IPRange {
+    /// Creates a new IP range
+    /// 
+    /// Parameters:
+    /// * `start`: first address of the range
+    /// * `end`: last address of the range
+    pub fn new(start: IPAddress, end: IPAddress) -> Result<IPRange, RangeError> {
+        if start.to_u32() > end.to_u32() {
+            return Err(RangeError::Reversed { start: start.to_string(), end: end.to_string() });
+        }
+
+        Ok(IPRange::from_u32(start.to_u32(), end.to_u32()))
+    }
+
+    /// Creates a range holding every address of a network
+    /// 
+    /// Parameters:
+    /// * `network`: network to convert
+    pub fn from_network(network: &IPNetwork) -> IPRange {
+        let (start, end) = network.bounds();
+        IPRange::from_u32(start, end)
+    }
+
+    /// Creates a range from a list of networks, which together have to cover a
+    /// single contiguous run of addresses
+    /// 
+    /// Parameters:
+    /// * `networks`: networks to convert
+    pub fn from_cidrs(networks: &[IPNetwork]) -> Result<IPRange, RangeError> {
+        let aggregated = aggregate(networks);
+        let (first, last) = match (aggregated.first(), aggregated.last()) {
+            (Some(first), Some(last)) => (first.bounds().0, last.bounds().1),
+            _ => return Err(RangeError::Netmask { source: NetmaskError::NoNetworks }),
+        };
+
+        // Aggregated blocks are disjoint, so they leave no gap only when their
+        // sizes add up to the size of the whole range
+        let range = IPRange::from_u32(first, last);
+        let mut covered = 0;
+        for network in &aggregated {
+            let (start, end) = network.bounds();
+            covered += (end - start) as u64 + 1;
+        }
+
+        if covered != range.len() {
+            return Err(RangeError::NotContiguous);
+        }
+
+        Ok(range)
+    }
+
+    /// Construct an IP range from string parameter, as `a-b` or `a - b`.
+    /// Fails with `Parse` on a malformed endpoint and with `Reversed` when the
+    /// start comes after the end.
+    /// 
+    /// Parameters:
+    /// * `range`: String value with IP range
+    pub fn from_string(range: String) -> Result<IPRange, RangeError> {
+        let separator = match range.find('-') {
+            Some(i) => i,
+            None => return Err(ParseError::MissingComponent { position: "range separator".to_string(), offset: range.len() }.into()),
+        };
+
+        // Offsets of errors are reported against the whole range string
+        let (start, end) = (&range[..separator], &range[separator + 1..]);
+        let start_offset = start.len() - start.trim_start().len();
+        let end_offset = separator + 1 + end.len() - end.trim_start().len();
+
+        let start = IPAddress::from_str(start.trim()).map_err(|e| e.offset_by(start_offset))?;
+        let end = IPAddress::from_str(end.trim()).map_err(|e| e.offset_by(end_offset))?;
+
+        IPRange::new(start, end)
+    }
+
+    /// Converts this range into the minimal list of CIDR blocks covering it
+    pub fn to_cidrs(&self) -> Vec<IPNetwork> {
+        range_to_cidrs(self.start.to_u32(), self.end.to_u32())
+    }
+
+    /// Number of addresses in this range
+    // A range always holds at least one address, so there is no is_empty()
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        (self.end.to_u32() - self.start.to_u32()) as u64 + 1
+    }
+
+    /// Checks whether an address lies inside this range
+    /// 
+    /// Parameters:
+    /// * `address`: address to look for
+    pub fn contains(&self, address: &IPAddress) -> bool {
+        (self.start.to_u32()..=self.end.to_u32()).contains(&address.to_u32())
+    }
+
+    /// Returns the addresses shared by this range and another one, if any
+    /// 
+    /// Parameters:
+    /// * `other`: range to intersect with
+    pub fn intersection(&self, other: &IPRange) -> Option<IPRange> {
+        let start = self.start.to_u32().max(other.start.to_u32());
+        let end = self.end.to_u32().min(other.end.to_u32());
+
+        if start > end {
+            return None;
+        }
+
+        Some(IPRange::from_u32(start, end))
+    }
+
+    /// Creates a range from numeric bounds, which must already be ordered
+    pub(crate) fn from_u32(start: u32, end: u32) -> IPRange {
+        IPRange { start: IPAddress::from_u32(start), end: IPAddress::from_u32(end) }
+    }
+}
+
+impl FromStr for IPRange {
+    type Err = RangeError;
+
+    /// Constructs an IP range from string slice, as `a-b` or `a - b`
+    fn from_str(range: &str) -> Result<IPRange, RangeError> {
+        IPRange::from_string(range.to_string())
+    }
+}
+
+impl fmt::Display for IPRange {
+    /// Formats an IP range as `a-b`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
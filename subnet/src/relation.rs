@@ -0,0 +1,76 @@
+use std::cmp::Ordering;
+use crate::network::IPNetwork;
+use crate::types::IPAddress;
+
+impl IPNetwork {
+    /// Returns the first and last address of the subnet of this network
+    pub(crate) fn bounds(&self) -> (u32, u32) {
+        let network = self.calculate_subnet().address.to_u32();
+        (network, network | self.prefix.wildcard().to_u32())
+    }
+
+    /// Checks whether an address lies inside the subnet of this network
+    /// 
+    /// Parameters:
+    /// * `address`: address to look for
+    pub fn contains(&self, address: &IPAddress) -> bool {
+        let (start, end) = self.bounds();
+        (start..=end).contains(&address.to_u32())
+    }
+
+    /// Checks whether a whole network lies inside the subnet of this network
+    /// 
+    /// Parameters:
+    /// * `other`: network to look for
+    pub fn contains_network(&self, other: &IPNetwork) -> bool {
+        let (start, end) = self.bounds();
+        let (other_start, other_end) = other.bounds();
+
+        start <= other_start && other_end <= end
+    }
+
+    /// Checks whether the subnet of this network lies inside another network
+    /// 
+    /// Parameters:
+    /// * `other`: network to check against
+    pub fn is_subnet_of(&self, other: &IPNetwork) -> bool {
+        other.contains_network(self)
+    }
+
+    /// Checks whether the subnet of this network shares at least one address
+    /// with another network
+    /// 
+    /// Parameters:
+    /// * `other`: network to check against
+    pub fn overlaps(&self, other: &IPNetwork) -> bool {
+        let (start, end) = self.bounds();
+        let (other_start, other_end) = other.bounds();
+
+        start <= other_end && other_start <= end
+    }
+
+    /// Checks whether the subnet of this network ends right before another
+    /// network starts, or starts right after it ends
+    /// 
+    /// Parameters:
+    /// * `other`: network to check against
+    pub fn is_adjacent(&self, other: &IPNetwork) -> bool {
+        let (start, end) = self.bounds();
+        let (other_start, other_end) = other.bounds();
+
+        end.checked_add(1) == Some(other_start) || other_end.checked_add(1) == Some(start)
+    }
+
+    /// Orders networks by network address, then by CIDR value so that a
+    /// supernet sorts right before its first subnet. Unlike `Ord`, host bits
+    /// are ignored.
+    /// 
+    /// Parameters:
+    /// * `other`: network to compare with
+    pub fn network_cmp(&self, other: &IPNetwork) -> Ordering {
+        let (start, _) = self.bounds();
+        let (other_start, _) = other.bounds();
+
+        start.cmp(&other_start).then(self.prefix.cmp(&other.prefix))
+    }
+}
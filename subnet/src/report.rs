@@ -0,0 +1,327 @@
+use std::fmt::Write;
+use std::str::FromStr;
+use crate::aggregate::Supernet;
+use crate::cloud::CloudSubnet;
+use crate::lint::Diagnostic;
+use crate::network::IPNetwork;
+use crate::summary::SubnetSummary;
+use crate::types::ParseError;
+use crate::vlsm::Allocation;
+
+/// Version of the report schema. Field names and report kinds only ever change
+/// along with this version, so that scripts can rely on them.
+pub const REPORT_VERSION: u32 = 2;
+
+/// Output format of a report
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// JSON object holding the schema version, the report kind and its records
+    Json,
+    /// YAML document, same layout as JSON
+    Yaml,
+    /// Comma separated values with a header line
+    Csv,
+    /// Tab separated values with a header line
+    Tsv,
+    /// Aligned text table with a header line
+    Table,
+}
+
+/// Single value of a report record
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// Text value, e.g. an address
+    Text(String),
+    /// Numeric value, e.g. a host count
+    Number(u64),
+    /// Missing value, e.g. the broadcast address of a /31
+    Null,
+}
+
+/// Computed result laid out as records sharing the same fields, ready to be
+/// rendered in any output format
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    /// Kind of report, e.g. `summary` or `vlsm`
+    pub kind: &'static str,
+    /// Field names, in column order
+    pub fields: Vec<&'static str>,
+    /// Values of every record, in the order of `fields`
+    pub records: Vec<Vec<Value>>,
+}
+
+impl Report {
+    /// Creates a new empty report
+    /// 
+    /// Parameters:
+    /// * `kind`: kind of report
+    /// * `fields`: field names, in column order
+    pub fn new(kind: &'static str, fields: &[&'static str]) -> Report {
+        Report { kind, fields: fields.to_vec(), records: Vec::new() }
+    }
+
+    /// Creates a report holding a single subnet summary
+    /// 
+    /// Parameters:
+    /// * `summary`: subnet summary to report
+    pub fn summary(summary: &SubnetSummary) -> Report {
+        let mut report = Report::new("summary", &["address", "network", "netmask", "wildcard", "broadcast", "first_host", "last_host", "total_hosts", "usable_hosts", "class", "classful", "type"]);
+        report.records.push(vec![
+            text(summary.address),
+            text(summary.network),
+            text(summary.netmask),
+            text(summary.wildcard),
+            summary.broadcast.map(text).unwrap_or(Value::Null),
+            text(summary.first_host),
+            text(summary.last_host),
+            Value::Number(summary.total_hosts),
+            Value::Number(summary.usable_hosts),
+            text(summary.address.class()),
+            text(summary.network.classful()),
+            Value::Text(summary.address.address_type()),
+        ]);
+
+        report
+    }
+
+    /// Creates a report describing a list of networks, like the result of a
+    /// split or of an aggregation
+    /// 
+    /// Parameters:
+    /// * `kind`: kind of report, e.g. `split` or `aggregate`
+    /// * `networks`: networks to report
+    pub fn networks(kind: &'static str, networks: &[IPNetwork]) -> Report {
+        let mut report = Report::new(kind, &["network", "netmask", "broadcast", "first_host", "last_host", "total_hosts", "usable_hosts"]);
+        for network in networks {
+            let summary = network.summary();
+            report.records.push(vec![
+                text(summary.network),
+                text(summary.netmask),
+                summary.broadcast.map(text).unwrap_or(Value::Null),
+                text(summary.first_host),
+                text(summary.last_host),
+                Value::Number(summary.total_hosts),
+                Value::Number(summary.usable_hosts),
+            ]);
+        }
+
+        report
+    }
+
+    /// Creates a report holding a VLSM allocation table
+    /// 
+    /// Parameters:
+    /// * `allocations`: allocations to report
+    pub fn vlsm(allocations: &[Allocation]) -> Report {
+        let mut report = Report::new("vlsm", &["name", "hosts", "network", "netmask", "broadcast", "spare_hosts"]);
+        for allocation in allocations {
+            report.records.push(vec![
+                Value::Text(allocation.name.clone()),
+                Value::Number(allocation.hosts as u64),
+                text(allocation.network),
+                text(allocation.netmask),
+                allocation.broadcast.map(text).unwrap_or(Value::Null),
+                Value::Number(allocation.spare_hosts),
+            ]);
+        }
+
+        report
+    }
+
+    /// Creates a report holding a single summary route
+    /// 
+    /// Parameters:
+    /// * `supernet`: summary route to report
+    pub fn supernet(supernet: &Supernet) -> Report {
+        let mut report = Report::new("supernet", &["network", "extra_addresses"]);
+        report.records.push(vec![text(supernet.network), Value::Number(supernet.extra_addresses)]);
+        report
+    }
+
+    /// Creates a report holding linter diagnostics
+    /// 
+    /// Parameters:
+    /// * `diagnostics`: diagnostics to report
+    pub fn diagnostics(diagnostics: &[Diagnostic]) -> Report {
+        let mut report = Report::new("lint", &["severity", "code", "entries", "message"]);
+        for diagnostic in diagnostics {
+            report.records.push(vec![
+                Value::Text(diagnostic.severity.to_string()),
+                Value::Text(diagnostic.code.to_string()),
+                Value::Text(diagnostic.entries.join(",")),
+                Value::Text(diagnostic.message.clone()),
+            ]);
+        }
+
+        report
+    }
+
+    /// Creates a report holding a cloud subnet plan
+    /// 
+    /// Parameters:
+    /// * `subnets`: planned subnets
+    pub fn cloud(subnets: &[CloudSubnet]) -> Report {
+        let mut report = Report::new("cloud", &["zone", "tier", "network", "netmask", "first_usable", "last_usable", "reserved", "usable_hosts"]);
+        for subnet in subnets {
+            report.records.push(vec![
+                Value::Text(subnet.zone.clone()),
+                Value::Text(subnet.tier.clone()),
+                text(subnet.network),
+                text(subnet.network.netmask()),
+                text(subnet.first_usable),
+                text(subnet.last_usable),
+                Value::Number(subnet.reserved.len() as u64),
+                Value::Number(subnet.usable_hosts),
+            ]);
+        }
+
+        report
+    }
+
+    /// Renders this report in the given format
+    /// 
+    /// Parameters:
+    /// * `format`: output format
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Json => self.to_json(),
+            Format::Yaml => self.to_yaml(),
+            Format::Csv => self.to_separated(',', csv_escape),
+            Format::Tsv => self.to_separated('\t', tsv_escape),
+            Format::Table => self.to_table(),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let records: Vec<String> = self.records.iter().map(|record| {
+            let fields: Vec<String> = self.fields.iter().zip(record).map(|(field, value)| format!("\"{}\":{}", field, json_value(value))).collect();
+            format!("{{{}}}", fields.join(","))
+        }).collect();
+
+        format!("{{\"version\":{},\"kind\":\"{}\",\"records\":[{}]}}\n", REPORT_VERSION, self.kind, records.join(","))
+    }
+
+    fn to_yaml(&self) -> String {
+        let mut output = format!("version: {}\nkind: {}\n", REPORT_VERSION, self.kind);
+        if self.records.is_empty() {
+            output.push_str("records: []\n");
+            return output;
+        }
+
+        output.push_str("records:\n");
+        for record in &self.records {
+            for (i, (field, value)) in self.fields.iter().zip(record).enumerate() {
+                let indent = if i == 0 { "  - " } else { "    " };
+                // JSON scalars are valid YAML scalars
+                let _ = writeln!(output, "{}{}: {}", indent, field, json_value(value));
+            }
+        }
+
+        output
+    }
+
+    fn to_separated(&self, separator: char, escape: fn(&str) -> String) -> String {
+        let mut output = String::new();
+        let header: Vec<String> = self.fields.iter().map(|field| escape(field)).collect();
+        let _ = writeln!(output, "{}", header.join(&separator.to_string()));
+
+        for record in &self.records {
+            let cells: Vec<String> = record.iter().map(|value| match value {
+                Value::Text(text) => escape(text),
+                Value::Number(number) => number.to_string(),
+                Value::Null => String::new(),
+            }).collect();
+            let _ = writeln!(output, "{}", cells.join(&separator.to_string()));
+        }
+
+        output
+    }
+
+    fn to_table(&self) -> String {
+        let mut rows = vec![self.fields.iter().map(|field| field.to_string()).collect::<Vec<String>>()];
+        for record in &self.records {
+            rows.push(record.iter().map(|value| match value {
+                Value::Text(text) => text.clone(),
+                Value::Number(number) => number.to_string(),
+                Value::Null => "-".to_string(),
+            }).collect());
+        }
+
+        let mut widths = vec![0; self.fields.len()];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut output = String::new();
+        for row in rows {
+            let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, &width)| format!("{:<width$}", cell, width = width)).collect();
+            let _ = writeln!(output, "{}", cells.join("  ").trim_end());
+        }
+
+        output
+    }
+}
+
+impl FromStr for Format {
+    type Err = ParseError;
+
+    /// Constructs a format from its name: `json`, `yaml`, `csv`, `tsv` or `table`
+    fn from_str(format: &str) -> Result<Format, ParseError> {
+        match format.to_ascii_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "csv" => Ok(Format::Csv),
+            "tsv" => Ok(Format::Tsv),
+            "table" => Ok(Format::Table),
+            _ => Err(ParseError::GenericError { position: "output format".to_string(), value: format.to_string(), offset: 0 }),
+        }
+    }
+}
+
+/// Wraps any displayable value into a text value
+fn text<T: ToString>(value: T) -> Value {
+    Value::Text(value.to_string())
+}
+
+/// Renders a value as a JSON scalar
+pub(crate) fn json_value(value: &Value) -> String {
+    match value {
+        Value::Text(text) => {
+            let mut output = String::from("\"");
+            for c in text.chars() {
+                match c {
+                    '"' => output.push_str("\\\""),
+                    '\\' => output.push_str("\\\\"),
+                    '\n' => output.push_str("\\n"),
+                    '\r' => output.push_str("\\r"),
+                    '\t' => output.push_str("\\t"),
+                    c if (c as u32) < 0x20 => {
+                        let _ = write!(output, "\\u{:04x}", c as u32);
+                    }
+                    c => output.push(c),
+                }
+            }
+
+            output.push('"');
+            output
+        }
+        Value::Number(number) => number.to_string(),
+        Value::Null => "null".to_string(),
+    }
+}
+
+/// Quotes a CSV cell when it holds a separator, a quote or a line break (RFC 4180)
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", cell.replace('"', "\"\""));
+    }
+
+    cell.to_string()
+}
+
+/// Escapes tabs, line breaks and backslashes of a TSV cell
+fn tsv_escape(cell: &str) -> String {
+    cell.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
@@ -0,0 +1,271 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{SerializeTuple, Serializer};
+use serde::{Deserialize, Serialize};
+use crate::constants::V6_SEGMENTS;
+use crate::network::{IPNetwork, IPv6Network, IPv6PrefixLen, PrefixLen};
+use crate::range::IPRange;
+use crate::set::IpSet;
+use crate::types::{IPAddress, IPv6Address, IPv6SubnetMask, SubnetMask, WildcardMask};
+
+// Human readable formats (JSON, TOML, YAML...) store every type as its
+// canonical string and parse it back with the strict parser. Binary formats
+// store a fixed size tuple of bytes, e.g. 5 bytes for an IPNetwork.
+
+/// Fixed size binary form of a type
+trait Compact: Sized {
+    /// Number of bytes of the binary form
+    const LEN: usize;
+
+    /// Converts a value into its binary form
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Converts a binary form back into a value, validating it
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String>;
+}
+
+/// Reads both the string and the binary form of a type
+struct CompactVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for CompactVisitor<T>
+where
+    T: Compact + FromStr,
+    T::Err: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a string or {} bytes", T::LEN)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        T::from_str(value).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<T, E> {
+        if value.len() != T::LEN {
+            return Err(E::invalid_length(value.len(), &self));
+        }
+
+        T::from_bytes(value).map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let mut bytes = Vec::with_capacity(T::LEN);
+        while let Some(byte) = seq.next_element::<u8>()? {
+            if bytes.len() == T::LEN {
+                return Err(de::Error::invalid_length(T::LEN + 1, &self));
+            }
+
+            bytes.push(byte);
+        }
+
+        self.visit_bytes(&bytes)
+    }
+}
+
+/// Serializes a value as string or fixed size tuple, depending on the format
+fn serialize_compact<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Compact + fmt::Display,
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        return serializer.collect_str(value);
+    }
+
+    let mut tuple = serializer.serialize_tuple(T::LEN)?;
+    for byte in value.to_bytes() {
+        tuple.serialize_element(&byte)?;
+    }
+
+    tuple.end()
+}
+
+/// Deserializes a value from string or fixed size tuple, depending on the format
+fn deserialize_compact<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Compact + FromStr,
+    T::Err: fmt::Display,
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        return deserializer.deserialize_str(CompactVisitor(PhantomData));
+    }
+
+    deserializer.deserialize_tuple(T::LEN, CompactVisitor(PhantomData))
+}
+
+/// Implements Serialize and Deserialize on top of the Compact form
+macro_rules! compact_serde {
+    ($($t:ty),*) => {
+        $(
+            impl Serialize for $t {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serialize_compact(self, serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $t {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$t, D::Error> {
+                    deserialize_compact(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+compact_serde!(IPAddress, SubnetMask, WildcardMask, IPNetwork, IPv6Address, IPv6SubnetMask, IPv6Network, IPRange);
+
+impl Compact for IPAddress {
+    const LEN: usize = 4;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        vec![self.b0, self.b1, self.b2, self.b3]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<IPAddress, String> {
+        Ok(IPAddress::new(bytes[0], bytes[1], bytes[2], bytes[3]))
+    }
+}
+
+impl Compact for SubnetMask {
+    const LEN: usize = 4;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        vec![self.b0, self.b1, self.b2, self.b3]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<SubnetMask, String> {
+        Ok(SubnetMask::new(bytes[0], bytes[1], bytes[2], bytes[3]))
+    }
+}
+
+impl Compact for WildcardMask {
+    const LEN: usize = 4;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        vec![self.b0, self.b1, self.b2, self.b3]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<WildcardMask, String> {
+        Ok(WildcardMask::new(bytes[0], bytes[1], bytes[2], bytes[3]))
+    }
+}
+
+impl Compact for IPNetwork {
+    const LEN: usize = 5;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.address.to_bytes();
+        bytes.push(self.cidr());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<IPNetwork, String> {
+        let prefix = PrefixLen::new(bytes[4]).map_err(|e| e.to_string())?;
+        Ok(IPNetwork::new(IPAddress::from_bytes(&bytes[..4])?, prefix))
+    }
+}
+
+impl Compact for IPv6Address {
+    const LEN: usize = 16;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.segments.iter().flat_map(|s| s.to_be_bytes()).collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<IPv6Address, String> {
+        Ok(IPv6Address::new(segments(bytes)))
+    }
+}
+
+impl Compact for IPv6SubnetMask {
+    const LEN: usize = 16;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.segments.iter().flat_map(|s| s.to_be_bytes()).collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<IPv6SubnetMask, String> {
+        Ok(IPv6SubnetMask::new(segments(bytes)))
+    }
+}
+
+impl Compact for IPv6Network {
+    const LEN: usize = 17;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.address.to_bytes();
+        bytes.push(self.cidr());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<IPv6Network, String> {
+        let prefix = IPv6PrefixLen::new(bytes[16]).map_err(|e| e.to_string())?;
+        Ok(IPv6Network::new(IPv6Address::from_bytes(&bytes[..16])?, prefix))
+    }
+}
+
+impl Compact for IPRange {
+    const LEN: usize = 8;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.start.to_bytes();
+        bytes.extend(self.end.to_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<IPRange, String> {
+        IPRange::new(IPAddress::from_bytes(&bytes[..4])?, IPAddress::from_bytes(&bytes[4..])?).map_err(|e| e.to_string())
+    }
+}
+
+impl Serialize for PrefixLen {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.get())
+    }
+}
+
+impl<'de> Deserialize<'de> for PrefixLen {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<PrefixLen, D::Error> {
+        PrefixLen::new(u8::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for IPv6PrefixLen {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.get())
+    }
+}
+
+impl<'de> Deserialize<'de> for IPv6PrefixLen {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<IPv6PrefixLen, D::Error> {
+        IPv6PrefixLen::new(u8::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for IpSet {
+    /// Serializes a set as its list of normalized ranges
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.ranges())
+    }
+}
+
+impl<'de> Deserialize<'de> for IpSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<IpSet, D::Error> {
+        Ok(IpSet::from_ranges(&Vec::<IPRange>::deserialize(deserializer)?))
+    }
+}
+
+/// Reads big endian IPv6 segments out of 16 bytes
+fn segments(bytes: &[u8]) -> [u16; V6_SEGMENTS] {
+    let mut segments = [0; V6_SEGMENTS];
+    for (segment, pair) in segments.iter_mut().zip(bytes.chunks_exact(2)) {
+        *segment = u16::from_be_bytes([pair[0], pair[1]]);
+    }
+
+    segments
+}
@@ -0,0 +1,161 @@
+use crate::aggregate::merge_ranges;
+use crate::network::IPNetwork;
+use crate::range::IPRange;
+use crate::types::IPAddress;
+
+/// A set of IP addresses, stored as sorted, disjoint and non adjacent ranges
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct IpSet {
+    /// Inclusive numeric bounds of every range in the set
+    ranges: Vec<(u32, u32)>,
+}
+
+impl IpSet {
+    /// Creates a new empty set
+    pub fn new() -> IpSet {
+        IpSet::default()
+    }
+
+    /// Creates a set holding every address of the given networks
+    /// 
+    /// Parameters:
+    /// * `networks`: networks to add
+    pub fn from_cidrs(networks: &[IPNetwork]) -> IpSet {
+        IpSet::normalized(networks.iter().map(|network| network.bounds()).collect())
+    }
+
+    /// Creates a set holding every address of the given ranges
+    /// 
+    /// Parameters:
+    /// * `ranges`: ranges to add
+    pub fn from_ranges(ranges: &[IPRange]) -> IpSet {
+        IpSet::normalized(ranges.iter().map(|r| (r.start.to_u32(), r.end.to_u32())).collect())
+    }
+
+    /// Adds every address of a network to this set
+    /// 
+    /// Parameters:
+    /// * `network`: network to add
+    pub fn insert(&mut self, network: &IPNetwork) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.push(network.bounds());
+        *self = IpSet::normalized(ranges);
+    }
+
+    /// Adds every address of a range to this set
+    /// 
+    /// Parameters:
+    /// * `range`: range to add
+    pub fn insert_range(&mut self, range: &IPRange) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.push((range.start.to_u32(), range.end.to_u32()));
+        *self = IpSet::normalized(ranges);
+    }
+
+    /// Checks whether an address belongs to this set
+    /// 
+    /// Parameters:
+    /// * `address`: address to look for
+    pub fn contains(&self, address: &IPAddress) -> bool {
+        let address = address.to_u32();
+        let index = self.ranges.partition_point(|&(_, end)| end < address);
+
+        self.ranges.get(index).is_some_and(|&(start, _)| start <= address)
+    }
+
+    /// Number of addresses in this set
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|&(start, end)| (end - start) as u64 + 1).sum()
+    }
+
+    /// Checks whether this set holds no address at all
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the normalized ranges of this set, in ascending order
+    pub fn ranges(&self) -> Vec<IPRange> {
+        self.ranges.iter().map(|&(start, end)| IPRange::from_u32(start, end)).collect()
+    }
+
+    /// Returns an iterator over the minimal list of CIDR blocks covering this set
+    pub fn cidrs(&self) -> impl Iterator<Item = IPNetwork> + '_ {
+        self.ranges.iter().flat_map(|&(start, end)| IPRange::from_u32(start, end).to_cidrs())
+    }
+
+    /// Returns every address that is in this set or in the other one
+    /// 
+    /// Parameters:
+    /// * `other`: set to join with
+    pub fn union(&self, other: &IpSet) -> IpSet {
+        IpSet::normalized(self.ranges.iter().chain(other.ranges.iter()).copied().collect())
+    }
+
+    /// Returns every address that is both in this set and in the other one
+    /// 
+    /// Parameters:
+    /// * `other`: set to intersect with
+    pub fn intersection(&self, other: &IpSet) -> IpSet {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (start, end) = self.ranges[i];
+            let (other_start, other_end) = other.ranges[j];
+
+            if start.max(other_start) <= end.min(other_end) {
+                ranges.push((start.max(other_start), end.min(other_end)));
+            }
+
+            // Move past whichever range ends first
+            if end < other_end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        IpSet { ranges }
+    }
+
+    /// Returns every address in this set that is not in the other one
+    /// 
+    /// Parameters:
+    /// * `other`: set of addresses to remove
+    pub fn difference(&self, other: &IpSet) -> IpSet {
+        self.intersection(&other.complement())
+    }
+
+    /// Returns every address that is in exactly one of the two sets
+    /// 
+    /// Parameters:
+    /// * `other`: set to compare with
+    pub fn symmetric_difference(&self, other: &IpSet) -> IpSet {
+        self.difference(other).union(&other.difference(self))
+    }
+
+    /// Returns every address of 0.0.0.0/0 that is not in this set
+    pub fn complement(&self) -> IpSet {
+        let mut ranges = Vec::with_capacity(self.ranges.len() + 1);
+        let mut cursor = 0_u64;
+
+        for &(start, end) in &self.ranges {
+            if cursor < start as u64 {
+                ranges.push((cursor as u32, start - 1));
+            }
+
+            cursor = end as u64 + 1;
+        }
+
+        if cursor <= u32::MAX as u64 {
+            ranges.push((cursor as u32, u32::MAX));
+        }
+
+        IpSet { ranges }
+    }
+
+    /// Sorts ranges and merges the overlapping or adjacent ones
+    fn normalized(ranges: Vec<(u32, u32)>) -> IpSet {
+        IpSet { ranges: merge_ranges(ranges) }
+    }
+}
@@ -0,0 +1,295 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use custom_error::custom_error;
+use crate::network::{IPNetwork, PrefixLen};
+use crate::trie::PrefixTrie;
+use crate::types::IPAddress;
+
+/// Snapshot of the IANA IPv4 Special-Purpose Address Registry, in the CSV
+/// layout published at https://www.iana.org/assignments/iana-ipv4-special-registry/
+const BUILTIN_REGISTRY: &str = include_str!("../data/iana-ipv4-special-registry-1.csv");
+
+custom_error!{
+    /// Describes an error while loading a special-purpose registry
+    pub RegistryError
+        Io{source: std::io::Error} = "Unable to read registry: {source}",
+        Malformed{line: usize, message: String} = "Malformed registry at line {line}: {message}"
+}
+
+/// A block of the IANA IPv4 special-purpose registry, along with its flags.
+/// Flags are `None` when the registry does not set them, e.g. for terminated
+/// blocks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpecialBlock {
+    /// Network of the block
+    pub network: IPNetwork,
+    /// Name of the block, e.g. `Private-Use`
+    pub name: String,
+    /// Defining RFC(s), e.g. `[RFC1918]`
+    pub rfc: String,
+    /// Whether an address of the block is valid as source address
+    pub source: Option<bool>,
+    /// Whether an address of the block is valid as destination address
+    pub destination: Option<bool>,
+    /// Whether routers may forward packets holding an address of the block
+    pub forwardable: Option<bool>,
+    /// Whether an address of the block is reachable beyond its local domain,
+    /// `None` when the registry does not define it, as for multicast
+    pub globally_reachable: Option<bool>,
+    /// Whether the block is reserved by the IP protocol itself
+    pub reserved_by_protocol: Option<bool>,
+}
+
+/// Special-purpose blocks, looked up by longest prefix match so that the most
+/// specific block wins, e.g. `192.0.0.9/32` within `192.0.0.0/24`
+#[derive(Clone)]
+pub struct SpecialRegistry {
+    blocks: PrefixTrie<SpecialBlock>,
+}
+
+impl SpecialRegistry {
+    /// Returns the registry built into the library
+    pub fn builtin() -> &'static SpecialRegistry {
+        static BUILTIN: OnceLock<SpecialRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(|| SpecialRegistry::from_csv(BUILTIN_REGISTRY).expect("Built-in registry is valid"))
+    }
+
+    /// Parses a registry from the IANA CSV file content. Multicast space
+    /// (224.0.0.0/4), which IANA tracks in a separate registry, is always
+    /// included.
+    /// 
+    /// Parameters:
+    /// * `csv`: content of `iana-ipv4-special-registry-1.csv`
+    pub fn from_csv(csv: &str) -> Result<SpecialRegistry, RegistryError> {
+        let mut registry = SpecialRegistry { blocks: PrefixTrie::new() };
+        registry.insert(SpecialBlock {
+            network: block(0xE0000000, 4),
+            name: "Multicast".to_string(),
+            rfc: "[RFC5771]".to_string(),
+            source: Some(false),
+            destination: Some(true),
+            forwardable: Some(true),
+            globally_reachable: None,
+            reserved_by_protocol: Some(false),
+        });
+
+        for (line, fields) in csv_records(csv).into_iter().skip(1) {
+            if fields.iter().all(|f| f.trim().is_empty()) {
+                continue;
+            }
+
+            if fields.len() < 10 {
+                return Err(RegistryError::Malformed { line, message: format!("expected 10 fields, got {}", fields.len()) });
+            }
+
+            let flag = |i: usize| parse_flag(&fields[i]).map_err(|message| RegistryError::Malformed { line, message });
+            let (source, destination, forwardable, globally_reachable, reserved_by_protocol) = (flag(5)?, flag(6)?, flag(7)?, flag(8)?, flag(9)?);
+
+            // A cell may hold several blocks, each possibly followed by a footnote
+            for block in fields[0].split(',') {
+                let block = strip_footnote(block);
+                let network = IPNetwork::from_str(block).map_err(|e| RegistryError::Malformed { line, message: e.to_string() })?;
+
+                registry.insert(SpecialBlock {
+                    network,
+                    name: fields[1].trim().trim_matches('"').to_string(),
+                    rfc: fields[2].split_whitespace().collect::<Vec<&str>>().join(" "),
+                    source,
+                    destination,
+                    forwardable,
+                    globally_reachable,
+                    reserved_by_protocol,
+                });
+            }
+        }
+
+        Ok(registry)
+    }
+
+    /// Loads a registry from an IANA CSV file, to keep the classification up
+    /// to date without a new release of the library
+    /// 
+    /// Parameters:
+    /// * `path`: path of `iana-ipv4-special-registry-1.csv`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<SpecialRegistry, RegistryError> {
+        SpecialRegistry::from_csv(&fs::read_to_string(path)?)
+    }
+
+    /// Adds a block to the registry, replacing any block with the same network
+    /// 
+    /// Parameters:
+    /// * `block`: block to add
+    pub fn insert(&mut self, block: SpecialBlock) {
+        let network = block.network.calculate_subnet();
+        self.blocks.insert(&network, block);
+    }
+
+    /// Returns the most specific block holding an address, if any
+    /// 
+    /// Parameters:
+    /// * `address`: address to classify
+    pub fn lookup(&self, address: &IPAddress) -> Option<&SpecialBlock> {
+        self.blocks.longest_match(address).map(|(_, block)| block)
+    }
+
+    /// Returns every block of the registry, in address order
+    pub fn blocks(&self) -> Vec<&SpecialBlock> {
+        self.blocks.iter().map(|(_, block)| block).collect()
+    }
+}
+
+impl IPAddress {
+    /// Returns the most specific special-purpose block holding this address,
+    /// from the built-in registry
+    pub fn special_purpose(&self) -> Option<&'static SpecialBlock> {
+        SpecialRegistry::builtin().lookup(self)
+    }
+
+    /// Checks whether this address is globally reachable, i.e. it is not in a
+    /// special-purpose block or its block is flagged as globally reachable.
+    /// Blocks whose flag the registry leaves undefined (`None`) count as not
+    /// globally reachable: this holds for the whole multicast space 224/4,
+    /// although some multicast scopes, like 233/8, are global.
+    pub fn is_global(&self) -> bool {
+        match self.special_purpose() {
+            Some(block) => block.globally_reachable.unwrap_or(false),
+            None => true,
+        }
+    }
+
+    /// Describes the kind of this address: `private` for RFC 1918 addresses,
+    /// the name of its special-purpose block, or `public`
+    pub fn address_type(&self) -> String {
+        if self.is_private() {
+            return "private".to_string();
+        }
+
+        match self.special_purpose() {
+            Some(block) => block.name.clone(),
+            None => "public".to_string(),
+        }
+    }
+
+    /// Checks whether this address is in "this network", 0.0.0.0/8 (RFC 791)
+    pub fn is_this_network(&self) -> bool {
+        block(0x00000000, 8).contains(self)
+    }
+
+    /// Checks whether this address is private-use, 10/8, 172.16/12 or 192.168/16 (RFC 1918)
+    pub fn is_private(&self) -> bool {
+        block(0x0A000000, 8).contains(self) || block(0xAC100000, 12).contains(self) || block(0xC0A80000, 16).contains(self)
+    }
+
+    /// Checks whether this address is in the shared (CGNAT) space, 100.64/10 (RFC 6598)
+    pub fn is_shared(&self) -> bool {
+        block(0x64400000, 10).contains(self)
+    }
+
+    /// Checks whether this address is a loopback address, 127/8 (RFC 1122)
+    pub fn is_loopback(&self) -> bool {
+        block(0x7F000000, 8).contains(self)
+    }
+
+    /// Checks whether this address is link-local, 169.254/16 (RFC 3927)
+    pub fn is_link_local(&self) -> bool {
+        block(0xA9FE0000, 16).contains(self)
+    }
+
+    /// Checks whether this address is in a documentation TEST-NET, 192.0.2/24,
+    /// 198.51.100/24 or 203.0.113/24 (RFC 5737)
+    pub fn is_documentation(&self) -> bool {
+        block(0xC0000200, 24).contains(self) || block(0xC6336400, 24).contains(self) || block(0xCB007100, 24).contains(self)
+    }
+
+    /// Checks whether this address is in the benchmarking space, 198.18/15 (RFC 2544)
+    pub fn is_benchmarking(&self) -> bool {
+        block(0xC6120000, 15).contains(self)
+    }
+
+    /// Checks whether this address is a multicast address, 224/4 (RFC 5771)
+    pub fn is_multicast(&self) -> bool {
+        block(0xE0000000, 4).contains(self)
+    }
+
+    /// Checks whether this address is in the reserved space, 240/4 (RFC 1112),
+    /// limited broadcast included
+    pub fn is_reserved(&self) -> bool {
+        block(0xF0000000, 4).contains(self)
+    }
+
+    /// Checks whether this address is the limited broadcast address, 255.255.255.255 (RFC 919)
+    pub fn is_broadcast(&self) -> bool {
+        self.to_u32() == u32::MAX
+    }
+}
+
+/// Returns a well known block given by its numeric base address and CIDR value
+fn block(base: u32, cidr: u8) -> IPNetwork {
+    IPNetwork::from_u32(base, PrefixLen::clamped(cidr))
+}
+
+/// Splits CSV content into records of fields, honouring quoted fields that
+/// hold separators, doubled quotes or line breaks. Every record comes with
+/// the line it starts at.
+fn csv_records(csv: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = csv.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                fields.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut fields)));
+                line += 1;
+                start = line;
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+
+                field.push(c);
+            }
+        }
+    }
+
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((start, fields));
+    }
+
+    records
+}
+
+/// Removes a trailing footnote reference, like ` [1]`, from a cell
+fn strip_footnote(cell: &str) -> &str {
+    let cell = cell.trim();
+    match cell.find('[') {
+        Some(i) => cell[..i].trim_end(),
+        None => cell,
+    }
+}
+
+/// Parses a registry flag: `True`, `False`, or empty / `N/A` when unset
+fn parse_flag(cell: &str) -> Result<Option<bool>, String> {
+    match strip_footnote(cell) {
+        "" | "N/A" => Ok(None),
+        "True" => Ok(Some(true)),
+        "False" => Ok(Some(false)),
+        other => Err(format!("invalid flag '{}'", other)),
+    }
+}
@@ -0,0 +1,44 @@
+use crate::constants::MAX_CIDR;
+use crate::network::IPNetwork;
+use crate::types::NetmaskError;
+
+impl IPNetwork {
+    /// Splits the subnet of this network into every child subnet with the
+    /// given CIDR value. Use [`IPNetwork::subnets`] to walk very large splits
+    /// without allocating them.
+    /// 
+    /// Parameters:
+    /// * `cidr`: CIDR value of child subnets
+    pub fn split(&self, cidr: u8) -> Result<Vec<IPNetwork>, NetmaskError> {
+        Ok(self.subnets(cidr)?.collect())
+    }
+
+    /// Splits the subnet of this network into the smallest number of equal
+    /// child subnets that is at least `count`
+    /// 
+    /// Parameters:
+    /// * `count`: minimum number of child subnets
+    pub fn split_into(&self, count: u32) -> Result<Vec<IPNetwork>, NetmaskError> {
+        self.split(self.split_cidr(count)?)
+    }
+
+    /// Calculates the CIDR value of the child subnets needed to split the subnet
+    /// of this network into at least `count` subnets
+    /// 
+    /// Parameters:
+    /// * `count`: minimum number of child subnets
+    pub fn split_cidr(&self, count: u32) -> Result<u8, NetmaskError> {
+        if count == 0 {
+            return Err(NetmaskError::InvalidSubnetCount { count });
+        }
+
+        // Bits to borrow: ceil(log2(count))
+        let bits = (u32::BITS - (count - 1).leading_zeros()) as u8;
+        let cidr = self.cidr() + bits;
+        if cidr > MAX_CIDR {
+            return Err(NetmaskError::MaxCidrExceeded { value: cidr });
+        }
+
+        Ok(cidr)
+    }
+}
@@ -0,0 +1,61 @@
+use crate::constants::MAX_CIDR;
+use crate::network::IPNetwork;
+use crate::types::{IPAddress, SubnetMask, WildcardMask};
+
+/// Full description of a subnet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubnetSummary {
+    /// IP address the summary was computed from
+    pub address: IPAddress,
+    /// Network of the subnet, host bits cleared
+    pub network: IPNetwork,
+    /// Subnet mask of the subnet
+    pub netmask: SubnetMask,
+    /// Wildcard (inverse) mask of the subnet
+    pub wildcard: WildcardMask,
+    /// Broadcast address. `None` for /31 (RFC 3021) and /32 subnets
+    pub broadcast: Option<IPAddress>,
+    /// First usable host address
+    pub first_host: IPAddress,
+    /// Last usable host address
+    pub last_host: IPAddress,
+    /// Total number of addresses in the subnet
+    pub total_hosts: u64,
+    /// Number of usable host addresses in the subnet
+    pub usable_hosts: u64,
+}
+
+impl IPNetwork {
+    /// Calculates the full summary of the subnet associated with this network
+    /// 
+    /// A /31 subnet is treated as a point-to-point link (RFC 3021): both addresses
+    /// are usable hosts and there is no broadcast address. A /32 subnet has a
+    /// single usable host, the address itself.
+    pub fn summary(&self) -> SubnetSummary {
+        let netmask = self.netmask();
+        let wildcard = netmask.to_wildcard();
+        let network = self.calculate_subnet();
+
+        let first = network.address.to_u32();
+        let last = first | wildcard.to_u32();
+        let total_hosts = 1_u64 << (MAX_CIDR - self.cidr());
+
+        let (first_host, last_host, broadcast, usable_hosts) = match self.cidr() {
+            32 => (first, first, None, 1),
+            31 => (first, last, None, 2),
+            _ => (first + 1, last - 1, Some(IPAddress::from_u32(last)), total_hosts - 2),
+        };
+
+        SubnetSummary {
+            address: self.address,
+            network,
+            netmask,
+            wildcard,
+            broadcast,
+            first_host: IPAddress::from_u32(first_host),
+            last_host: IPAddress::from_u32(last_host),
+            total_hosts,
+            usable_hosts,
+        }
+    }
+}
@@ -0,0 +1,494 @@
+use crate::constants::MAX_CIDR;
+use crate::network::{IPNetwork, PrefixLen};
+use crate::types::IPAddress;
+
+/// Marks a missing child in the node arena
+const NO_NODE: u32 = u32::MAX;
+
+/// Number of leading address bits resolved at once through the direct index
+const INDEX_BITS: u8 = 16;
+
+/// Single node of a path compressed binary trie
+#[derive(Clone)]
+struct Node<T> {
+    /// Network bits of the node, host bits are zero
+    prefix: u32,
+    /// CIDR value of the node
+    cidr: u8,
+    /// Value stored for this exact prefix, if any
+    value: Option<T>,
+    /// Indexes of the children whose next bit is 0 and 1
+    children: [u32; 2],
+}
+
+/// Entry of the direct index, one per value of the leading `INDEX_BITS` bits
+#[derive(Clone, Copy)]
+struct Slot {
+    /// Deepest node with CIDR up to `INDEX_BITS` on the path of the slot
+    node: u32,
+    /// Deepest node holding a value on the path down to `node`
+    best: u32,
+}
+
+/// Routing table mapping networks to values, with longest prefix match lookups
+/// 
+/// Prefixes are kept in a path compressed binary trie. A direct index over the
+/// leading 16 bits of the address skips the top of the trie, so a lookup only
+/// walks the few nodes below the /16 it falls in.
+#[derive(Clone)]
+pub struct PrefixTrie<T> {
+    /// Node arena, the root (0.0.0.0/0) is always at index 0
+    nodes: Vec<Node<T>>,
+    /// Direct index, allocated on first insertion
+    index: Vec<Slot>,
+    /// Indexes of arena slots left unused by removals
+    free: Vec<u32>,
+    /// Number of stored prefixes
+    len: usize,
+}
+
+impl<T> Default for PrefixTrie<T> {
+    fn default() -> Self {
+        PrefixTrie::new()
+    }
+}
+
+impl<T> PrefixTrie<T> {
+    /// Creates a new empty routing table
+    pub fn new() -> PrefixTrie<T> {
+        PrefixTrie { nodes: vec![Node { prefix: 0, cidr: 0, value: None, children: [NO_NODE; 2] }], index: Vec::new(), free: Vec::new(), len: 0 }
+    }
+
+    /// Number of stored prefixes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks whether no prefix is stored
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Stores a value for a network, returning the value it replaced if any.
+    /// Host bits of the network are ignored.
+    /// 
+    /// Parameters:
+    /// * `network`: network to store the value for
+    /// * `value`: value to store
+    pub fn insert(&mut self, network: &IPNetwork, value: T) -> Option<T> {
+        let (prefix, cidr) = key(network);
+        let (old, changed) = self.insert_node(prefix, cidr, value);
+
+        // Replacing a value leaves the shape of the trie untouched
+        if old.is_none() {
+            self.refresh(prefix, changed);
+        }
+
+        old
+    }
+
+    /// Returns the value stored for exactly this network, if any
+    /// 
+    /// Parameters:
+    /// * `network`: network to look for
+    pub fn get(&self, network: &IPNetwork) -> Option<&T> {
+        let (prefix, cidr) = key(network);
+        self.find(prefix, cidr).and_then(|(node, _, _)| self.nodes[node].value.as_ref())
+    }
+
+    /// Removes the value stored for exactly this network, returning it if any
+    /// 
+    /// Parameters:
+    /// * `network`: network to remove
+    pub fn remove(&mut self, network: &IPNetwork) -> Option<T> {
+        let (prefix, cidr) = key(network);
+        let (node, parent, grandparent) = self.find(prefix, cidr)?;
+
+        let value = self.nodes[node].value.take();
+        if value.is_some() {
+            self.len -= 1;
+            let changed = self.compact(node, parent, grandparent);
+            self.refresh(prefix, changed);
+        }
+
+        value
+    }
+
+    /// Finds the most specific stored network containing an address, along with
+    /// its value
+    /// 
+    /// Parameters:
+    /// * `address`: host address to route
+    pub fn longest_match(&self, address: &IPAddress) -> Option<(IPNetwork, &T)> {
+        let address = address.to_u32();
+
+        // Start right below the /16 of the address
+        let slot = (address >> (MAX_CIDR - INDEX_BITS)) as usize;
+        let (mut current, mut best) = match self.index.get(slot) {
+            Some(slot) => (slot.node as usize, slot.best),
+            None => (0, NO_NODE),
+        };
+
+        loop {
+            let node = &self.nodes[current];
+            if node.value.is_some() {
+                best = current as u32;
+            }
+
+            if node.cidr == MAX_CIDR {
+                break;
+            }
+
+            let child = node.children[bit_at(address, node.cidr)];
+            if child == NO_NODE {
+                break;
+            }
+
+            let child_node = &self.nodes[child as usize];
+            if mask(address, child_node.cidr) != child_node.prefix {
+                break;
+            }
+
+            current = child as usize;
+        }
+
+        let node = self.nodes.get(best as usize)?;
+        node.value.as_ref().map(|value| (node.network(), value))
+    }
+
+    /// Iterates over every stored network containing the given network (itself
+    /// included), least specific first
+    /// 
+    /// Parameters:
+    /// * `network`: network to look for
+    pub fn covering(&self, network: &IPNetwork) -> Covering<'_, T> {
+        let (prefix, cidr) = key(network);
+        Covering { trie: self, next: Some(0), prefix, cidr }
+    }
+
+    /// Iterates over every stored network contained in the given network
+    /// (itself included), in ascending address order
+    /// 
+    /// Parameters:
+    /// * `network`: network to look into
+    pub fn covered(&self, network: &IPNetwork) -> Iter<'_, T> {
+        let (prefix, cidr) = key(network);
+        let mut current = 0;
+
+        // Walk down to the first node inside the network
+        while self.nodes[current].cidr < cidr {
+            let child = self.nodes[current].children[bit_at(prefix, self.nodes[current].cidr)];
+            if child == NO_NODE {
+                return Iter { trie: self, stack: Vec::new() };
+            }
+
+            current = child as usize;
+        }
+
+        if mask(self.nodes[current].prefix, cidr) != prefix {
+            return Iter { trie: self, stack: Vec::new() };
+        }
+
+        Iter { trie: self, stack: vec![current] }
+    }
+
+    /// Iterates over every stored network with its value, in ascending address order
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { trie: self, stack: vec![0] }
+    }
+
+    /// Stores a value for a prefix, returning the value it replaced along with
+    /// the CIDR value of the least specific node that was added or changed
+    fn insert_node(&mut self, prefix: u32, cidr: u8, value: T) -> (Option<T>, u8) {
+        let mut current = 0;
+
+        loop {
+            if self.nodes[current].cidr == cidr {
+                let old = self.nodes[current].value.replace(value);
+                if old.is_none() {
+                    self.len += 1;
+                }
+
+                return (old, cidr);
+            }
+
+            let bit = bit_at(prefix, self.nodes[current].cidr);
+            let child = self.nodes[current].children[bit];
+
+            if child == NO_NODE {
+                let leaf = self.alloc(prefix, cidr, Some(value));
+                self.nodes[current].children[bit] = leaf;
+                self.len += 1;
+                return (None, cidr);
+            }
+
+            let child = child as usize;
+            let (child_prefix, child_cidr) = (self.nodes[child].prefix, self.nodes[child].cidr);
+            let common = common_cidr(prefix, child_prefix).min(cidr).min(child_cidr);
+
+            // Child lies on the path to the new prefix, keep walking
+            if common == child_cidr {
+                current = child;
+                continue;
+            }
+
+            // New prefix sits between this node and the child
+            if common == cidr {
+                let node = self.alloc(prefix, cidr, Some(value));
+                self.nodes[node as usize].children[bit_at(child_prefix, cidr)] = child as u32;
+                self.nodes[current].children[bit] = node;
+                self.len += 1;
+                return (None, cidr);
+            }
+
+            // Paths diverge below this node: add a branching node for both
+            let branch = self.alloc(mask(prefix, common), common, None);
+            let leaf = self.alloc(prefix, cidr, Some(value));
+            self.nodes[branch as usize].children[bit_at(prefix, common)] = leaf;
+            self.nodes[branch as usize].children[bit_at(child_prefix, common)] = child as u32;
+            self.nodes[current].children[bit] = branch;
+            self.len += 1;
+            return (None, common);
+        }
+    }
+
+    /// Finds the node of an exact prefix, along with the indexes of its parent
+    /// and grandparent (the root stands in for missing ancestors)
+    fn find(&self, prefix: u32, cidr: u8) -> Option<(usize, usize, usize)> {
+        let (mut current, mut parent, mut grandparent) = (0, 0, 0);
+
+        loop {
+            let node = &self.nodes[current];
+            if node.cidr == cidr {
+                return if node.prefix == prefix { Some((current, parent, grandparent)) } else { None };
+            }
+
+            if node.cidr > cidr || mask(prefix, node.cidr) != node.prefix {
+                return None;
+            }
+
+            let child = node.children[bit_at(prefix, node.cidr)];
+            if child == NO_NODE {
+                return None;
+            }
+
+            grandparent = parent;
+            parent = current;
+            current = child as usize;
+        }
+    }
+
+    /// Drops a node left without value unless it still branches. A parent
+    /// left without value and with a single child is dropped as well. Returns
+    /// the CIDR value of the least specific node that changed.
+    fn compact(&mut self, node: usize, parent: usize, grandparent: usize) -> u8 {
+        let cidr = self.nodes[node].cidr;
+        if node == 0 || self.nodes[node].children.iter().all(|&c| c != NO_NODE) {
+            return cidr;
+        }
+
+        let children = self.nodes[node].children;
+        let replacement = if children[0] != NO_NODE { children[0] } else { children[1] };
+        self.replace_child(parent, node, replacement);
+        self.release(node);
+
+        // Parent had two children, it is now a plain pass-through
+        if replacement == NO_NODE && parent != 0 && self.nodes[parent].value.is_none() {
+            let children = self.nodes[parent].children;
+            let only = if children[0] != NO_NODE { children[0] } else { children[1] };
+            let parent_cidr = self.nodes[parent].cidr;
+            self.replace_child(grandparent, parent, only);
+            self.release(parent);
+            return parent_cidr;
+        }
+
+        cidr
+    }
+
+    /// Points the link of a node to one of its children somewhere else
+    fn replace_child(&mut self, node: usize, child: usize, replacement: u32) {
+        for link in self.nodes[node].children.iter_mut() {
+            if *link == child as u32 {
+                *link = replacement;
+            }
+        }
+    }
+
+    /// Recomputes the direct index entries covered by a changed prefix
+    fn refresh(&mut self, prefix: u32, cidr: u8) {
+        if self.index.is_empty() {
+            self.index = vec![Slot { node: 0, best: NO_NODE }; 1 << INDEX_BITS];
+        }
+
+        // Walk down to the deepest node covering every entry to recompute
+        let cidr = cidr.min(INDEX_BITS);
+        let (mut current, mut best) = (0, NO_NODE);
+        loop {
+            let node = &self.nodes[current];
+            if node.cidr >= cidr {
+                break;
+            }
+
+            if node.value.is_some() {
+                best = current as u32;
+            }
+
+            let child = node.children[bit_at(prefix, node.cidr)];
+            if child == NO_NODE {
+                break;
+            }
+
+            let child_node = &self.nodes[child as usize];
+            if child_node.cidr > cidr || mask(prefix, child_node.cidr) != child_node.prefix {
+                break;
+            }
+
+            current = child as usize;
+        }
+
+        let first = (mask(prefix, cidr) >> (MAX_CIDR - INDEX_BITS)) as usize;
+        self.fill(current, best, first, first + (1 << (INDEX_BITS - cidr)) - 1);
+    }
+
+    /// Points the direct index entries of a node, clamped to `first..=last`,
+    /// to that node, then lets its children up to `INDEX_BITS` claim theirs
+    fn fill(&mut self, node: usize, best: u32, first: usize, last: usize) {
+        let best = if self.nodes[node].value.is_some() { node as u32 } else { best };
+        for slot in &mut self.index[first..=last] {
+            *slot = Slot { node: node as u32, best };
+        }
+
+        for child in self.nodes[node].children {
+            if child == NO_NODE || self.nodes[child as usize].cidr > INDEX_BITS {
+                continue;
+            }
+
+            let child_node = &self.nodes[child as usize];
+            let child_first = (child_node.prefix >> (MAX_CIDR - INDEX_BITS)) as usize;
+            let child_last = child_first + (1 << (INDEX_BITS - child_node.cidr)) - 1;
+
+            if child_first.max(first) <= child_last.min(last) {
+                self.fill(child as usize, best, child_first.max(first), child_last.min(last));
+            }
+        }
+    }
+
+    /// Returns an arena slot holding a new node
+    fn alloc(&mut self, prefix: u32, cidr: u8, value: Option<T>) -> u32 {
+        let node = Node { prefix, cidr, value, children: [NO_NODE; 2] };
+
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index as usize] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                (self.nodes.len() - 1) as u32
+            }
+        }
+    }
+
+    /// Marks an arena slot as unused
+    fn release(&mut self, node: usize) {
+        self.nodes[node].value = None;
+        self.nodes[node].children = [NO_NODE; 2];
+        self.free.push(node as u32);
+    }
+}
+
+/// Iterator over the stored networks containing a network, least specific first
+#[derive(Clone)]
+pub struct Covering<'a, T> {
+    trie: &'a PrefixTrie<T>,
+    /// Next node to visit on the path to the network
+    next: Option<usize>,
+    prefix: u32,
+    cidr: u8,
+}
+
+/// Iterator over the stored networks of a subtree, in ascending address order
+#[derive(Clone)]
+pub struct Iter<'a, T> {
+    trie: &'a PrefixTrie<T>,
+    /// Nodes left to visit, the next one on top
+    stack: Vec<usize>,
+}
+
+impl<'a, T> Iterator for Covering<'a, T> {
+    type Item = (IPNetwork, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(current) = self.next {
+            let node = &self.trie.nodes[current];
+
+            // Step down while the child still holds the network
+            self.next = None;
+            if node.cidr < self.cidr {
+                let child = node.children[bit_at(self.prefix, node.cidr)];
+                if child != NO_NODE {
+                    let child_node = &self.trie.nodes[child as usize];
+                    if child_node.cidr <= self.cidr && mask(self.prefix, child_node.cidr) == child_node.prefix {
+                        self.next = Some(child as usize);
+                    }
+                }
+            }
+
+            if let Some(value) = &node.value {
+                return Some((node.network(), value));
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (IPNetwork, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(current) = self.stack.pop() {
+            let node = &self.trie.nodes[current];
+
+            // Push the 1 branch first so that the 0 branch is visited first
+            for &child in node.children.iter().rev() {
+                if child != NO_NODE {
+                    self.stack.push(child as usize);
+                }
+            }
+
+            if let Some(value) = &node.value {
+                return Some((node.network(), value));
+            }
+        }
+
+        None
+    }
+}
+
+impl<T> Node<T> {
+    /// Returns the network of this node
+    fn network(&self) -> IPNetwork {
+        IPNetwork::from_u32(self.prefix, PrefixLen::clamped(self.cidr))
+    }
+}
+
+/// Converts a network into its trie key
+fn key(network: &IPNetwork) -> (u32, u8) {
+    (network.calculate_subnet().address.to_u32(), network.cidr())
+}
+
+/// Keeps the first `cidr` bits of a value
+fn mask(value: u32, cidr: u8) -> u32 {
+    value & u32::MAX.checked_shl((MAX_CIDR - cidr) as u32).unwrap_or(0)
+}
+
+/// Returns the bit following the first `cidr` bits of a value
+fn bit_at(value: u32, cidr: u8) -> usize {
+    ((value >> (MAX_CIDR - 1 - cidr)) & 1) as usize
+}
+
+/// Number of leading bits two values have in common
+fn common_cidr(a: u32, b: u32) -> u8 {
+    (a ^ b).leading_zeros() as u8
+}
@@ -1,24 +1,35 @@
-use std::num::ParseIntError;
 use custom_error::custom_error;
-use crate::constants::{UNDEF_CIDR, MAX_CIDR};
+use crate::constants::{MAX_CIDR, MAX_CIDR_V6, V6_SEGMENTS};
+use crate::network::{IPv6PrefixLen, PrefixLen};
+use crate::parse::{parse_dotted, reject_prefix, ParseOptions};
+use std::fmt;
+use std::str::FromStr;
 
 custom_error!{
     /// Describes a parsing error of some kind
     pub ParseError
-        GenericError{position: String, value: String} = "Error parsing value in {position}. It was '{value}'",
-        MaxCidrExceeded{value: u8} = "Maximum CIDR value exceeded. It was {value}"
+        GenericError{position: String, value: String, offset: usize} = "Error parsing value in {position} at byte {offset}. It was '{value}'",
+        MaxCidrExceeded{value: u8, offset: usize} = "Maximum CIDR value exceeded at byte {offset}. It was {value}",
+        EmptyComponent{position: String, offset: usize} = "Empty {position} at byte {offset}",
+        MissingComponent{position: String, offset: usize} = "Missing {position} at byte {offset}",
+        OutOfRange{position: String, value: String, offset: usize} = "Value of {position} out of range at byte {offset}. It was '{value}'",
+        LeadingZero{position: String, value: String, offset: usize} = "Leading zero in {position} at byte {offset}. It was '{value}'",
+        TrailingGarbage{value: String, offset: usize} = "Unexpected trailing input at byte {offset}. It was '{value}'"
 }
 
 custom_error!{
     /// Describes an error related with a SubnetMask type
     pub NetmaskError
-        UndefinedCidr = "Undefinded CIDR, cannot proceed",
         MaxCidrExceeded{value: u8} = "Maximum CIDR value exceeded. It was {value}",
-        CalculationError = "Unable to calculate netmask due to previous error"
+        ShorterThanParent{value: u8, parent: u8} = "CIDR value {value} is shorter than parent CIDR {parent}",
+        InvalidSubnetCount{count: u32} = "Cannot split network into {count} subnets",
+        NoNetworks = "No networks given, cannot proceed",
+        NonContiguous{mask: String} = "Mask {mask} is not contiguous"
 }
 
-/// Represents a single IP address
-#[derive(Clone, Copy)]
+/// Represents a single IP address. An address together with a prefix length
+/// is an [`IPNetwork`](crate::network::IPNetwork).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct IPAddress {
     /// First byte of IP address
     pub b0: u8,
@@ -28,12 +39,10 @@ pub struct IPAddress {
     pub b2: u8,
     /// Fourth byte of IP address
     pub b3: u8,
-    /// CIDR value of IP address
-    pub cidr: u8,
 }
 
 /// Represents a dot.decimal notation subnet mask
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SubnetMask {
     /// First byte of IP address
     pub b0: u8,
@@ -45,6 +54,35 @@ pub struct SubnetMask {
     pub b3: u8,
 }
 
+/// Represents a dot.decimal wildcard (inverse) mask, like the ones of Cisco
+/// ACLs. Set bits are "don't care" bits and may follow any pattern.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WildcardMask {
+    /// First byte of wildcard mask
+    pub b0: u8,
+    /// Second byte of wildcard mask
+    pub b1: u8,
+    /// Third byte of wildcard mask
+    pub b2: u8,
+    /// Fourth byte of wildcard mask
+    pub b3: u8,
+}
+
+/// Represents a single IPv6 address. An address together with a prefix length
+/// is an [`IPv6Network`](crate::network::IPv6Network).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IPv6Address {
+    /// 16 bit segments of IPv6 address, most significant first
+    pub segments: [u16; V6_SEGMENTS],
+}
+
+/// Represents a colon separated IPv6 subnet mask
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IPv6SubnetMask {
+    /// 16 bit segments of subnet mask, most significant first
+    pub segments: [u16; V6_SEGMENTS],
+}
+
 impl IPAddress {
     /// Creates a new IP address struct
     /// 
@@ -53,9 +91,12 @@ impl IPAddress {
     /// * `b1`: second byte of IP address
     /// * `b2`: third byte of IP address
     /// * `b3`: fourth byte of IP address
-    /// * `cidr`: CIDR value of IP address
-    pub fn new(b0: u8, b1: u8, b2: u8, b3: u8, cidr: u8) -> IPAddress {
-        IPAddress { b0, b1, b2, b3, cidr }
+    /// 
+    /// Addresses used to carry a CIDR value as fifth parameter. Build an
+    /// [`IPNetwork`](crate::network::IPNetwork) instead:
+    /// `IPNetwork::new(IPAddress::new(b0, b1, b2, b3), PrefixLen::new(cidr)?)`.
+    pub fn new(b0: u8, b1: u8, b2: u8, b3: u8) -> IPAddress {
+        IPAddress { b0, b1, b2, b3 }
     }
 
     /// Creates a new IP address struct
@@ -65,96 +106,77 @@ impl IPAddress {
     /// * `b1`: second byte of IP address
     /// * `b2`: third byte of IP address
     /// * `b3`: fourth byte of IP address
+    #[deprecated(note = "addresses never carry a CIDR value anymore, use `IPAddress::new`")]
     pub fn new_without_cidr(b0: u8, b1: u8, b2: u8, b3: u8) -> IPAddress {
-        IPAddress::new(b0, b1, b2, b3, UNDEF_CIDR)
+        IPAddress::new(b0, b1, b2, b3)
     }
 
     /// Construct an IP address from string parameter
     /// 
+    /// Parsing is strict: exactly four decimal bytes, no whitespace, signs or
+    /// leading zeros. A CIDR suffix is rejected, parse an
+    /// [`IPNetwork`](crate::network::IPNetwork) instead.
+    /// 
     /// Parameters:
-    /// * `ip_address`: String value with IP address. It may or may not contain CIDR value.
+    /// * `ip_address`: String value with IP address
     pub fn from_string(ip_address: String) -> Result<IPAddress, ParseError> {
-        // IP string separators
-        const SEP: [char; 2] = ['.', '/'];
-
-        // Split string into chunks
-        let chunks: Vec<&str> = ip_address.split(&SEP).collect();
-
-        // Try to parse all chunks
-        let b0 = chunks[0].parse();
-        if b0.is_err() {
-            return Err(ParseError::GenericError { position: "byte 0".to_string(), value: chunks[0].to_string() })
-        }
-
-        let b1 = chunks[1].parse();
-        if b1.is_err() {
-            return Err(ParseError::GenericError { position: "byte 1".to_string(), value: chunks[1].to_string() })
-        }
-
-        let b2 = chunks[2].parse();
-        if b2.is_err() {
-            return Err(ParseError::GenericError { position: "byte 2".to_string(), value: chunks[2].to_string() })
-        }
-
-        let b3 = chunks[3].parse();
-        if b3.is_err() {
-            return Err(ParseError::GenericError { position: "byte 3".to_string(), value: chunks[3].to_string() })
-        }
-
-        let mut cidr = UNDEF_CIDR;
-
-        // Check if we have to parse CIDR or not
-        if chunks.len() >= 5 { 
-            let v_cidr: Result<u8, ParseIntError> = chunks[4].parse();
-            if v_cidr.is_err() {
-                return Err(ParseError::GenericError { position: "CIDR value".to_string(), value: chunks[4].to_string() })
-            }
+        IPAddress::from_str_with_options(&ip_address, &ParseOptions::new())
+    }
 
-            // Check if CIDR does not exceed max allowed value
-            if v_cidr.as_ref().unwrap() > &MAX_CIDR {
-                return Err(ParseError::MaxCidrExceeded { value: v_cidr.unwrap() });
-            }
+    /// Constructs an IP address from string slice with custom parse options
+    /// 
+    /// Parameters:
+    /// * `ip_address`: string slice value with IP address
+    /// * `options`: parse options
+    pub fn from_str_with_options(ip_address: &str, options: &ParseOptions) -> Result<IPAddress, ParseError> {
+        reject_prefix(ip_address)?;
 
-            cidr = v_cidr.unwrap()
+        if options.inet_aton {
+            return Ok(IPAddress::from_inet_aton(ip_address)?.address);
         }
 
-
-        Ok(IPAddress::new(b0.unwrap(), b1.unwrap(), b2.unwrap(), b3.unwrap(), cidr))
+        let [b0, b1, b2, b3] = parse_dotted(ip_address, options)?;
+        Ok(IPAddress::new(b0, b1, b2, b3))
     }
 
-    /// Constructs an IP address from string slice
+    /// Constructs an IP address from its 32 bit numeric value
     /// 
     /// Parameters:
-    /// * `ip_address`: string slice value with IP address. It may or may not contain CIDR value.
-    pub fn from_str(ip_address: &str) -> Result<IPAddress, ParseError> {
-        IPAddress::from_string(ip_address.to_string())
+    /// * `value`: numeric value of IP address
+    pub fn from_u32(value: u32) -> IPAddress {
+        let [b0, b1, b2, b3] = value.to_be_bytes();
+        IPAddress::new(b0, b1, b2, b3)
     }
 
-    /// Converts an IP address into a standard formatted string (dot.decimal + CIDR)
-    pub fn to_string(&self) -> String {
-        if self.cidr != UNDEF_CIDR {
-            format!("{}.{}.{}.{}/{}", self.b0, self.b1, self.b2, self.b3, self.cidr)
-        } else {
-            format!("{}.{}.{}.{}", self.b0, self.b1, self.b2, self.b3)
-        }
+    /// Converts an IP address into its 32 bit numeric value
+    pub fn to_u32(&self) -> u32 {
+        u32::from_be_bytes([self.b0, self.b1, self.b2, self.b3])
     }
 
-    /// Calculates the subnet associated with this IP address
-    pub fn calculate_subnet(&self) -> Result<IPAddress, NetmaskError> {
-        let netmask = SubnetMask::from_cidr(self.cidr);
-        if netmask.is_err() {
-            return Err(netmask.err().unwrap());
-        }
+    /// Checks whether this IP address matches an address/wildcard pair, ACL
+    /// style: every bit not set in the wildcard has to be equal in both addresses
+    /// 
+    /// Parameters:
+    /// * `address`: address of the pair
+    /// * `wildcard`: wildcard mask of the pair
+    pub fn matches_wildcard(&self, address: &IPAddress, wildcard: &WildcardMask) -> bool {
+        (self.to_u32() ^ address.to_u32()) & !wildcard.to_u32() == 0
+    }
+}
 
-        let netmask = netmask.unwrap();
-        let mut result = *self;
+impl FromStr for IPAddress {
+    type Err = ParseError;
 
-        result.b0 &= netmask.b0;
-        result.b1 &= netmask.b1;
-        result.b2 &= netmask.b2;
-        result.b3 &= netmask.b3;
+    /// Constructs an IP address from string slice
+    fn from_str(ip_address: &str) -> Result<IPAddress, ParseError> {
+        IPAddress::from_str_with_options(ip_address, &ParseOptions::new())
+    }
+}
 
-        Ok(result)
+impl fmt::Display for IPAddress {
+    /// Formats an IP address as a standard dot.decimal string
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}.{}", self.b0, self.b1, self.b2, self.b3)
     }
 }
 
@@ -175,14 +197,15 @@ impl SubnetMask {
     /// Parameters:
     /// * `cidr`: CIDR decimal value
     pub fn from_cidr(cidr: u8) -> Result<SubnetMask, NetmaskError> {
-        if cidr == UNDEF_CIDR {
-            return Err(NetmaskError::UndefinedCidr);
-        }
-
-        if cidr > MAX_CIDR {
-            return Err(NetmaskError::MaxCidrExceeded { value: cidr })
-        }
+        Ok(SubnetMask::from_prefix(PrefixLen::new(cidr)?))
+    }
 
+    /// Constructs a new SubnetMask given a prefix length, which is always valid
+    /// 
+    /// Parameters:
+    /// * `prefix`: prefix length
+    pub fn from_prefix(prefix: PrefixLen) -> SubnetMask {
+        let cidr = prefix.get();
         let mut val = SubnetMask::new(0, 0, 0, 0);
 
         // Set bits for masking
@@ -196,58 +219,397 @@ impl SubnetMask {
         val.b2 = ((bits & 0xFF00) >> 8) as u8;
         val.b3 = (bits & 0xFF) as u8;
 
-        Ok(val)
+        val
     }
 
     /// Constructs a subnet mask from string
     /// 
+    /// Parsing is strict: exactly four decimal bytes, no whitespace, signs or
+    /// leading zeros.
+    /// 
     /// Parameters:
     /// * `netmask`: String value of subnet mask
     pub fn from_string(netmask: String) -> Result<SubnetMask, ParseError> {
-        const SEP: char = '.';
-        
-        // Split into chunks
-        let chunks: Vec<&str> = netmask.split(SEP).collect();
-
-        // Parse chunks and set values
-        let b0 = chunks[0].parse();
-        if b0.is_err() {
-            return Err(ParseError::GenericError { position: "byte 0".to_string(), value: chunks[0].to_string() })
-        }
+        SubnetMask::from_str_with_options(&netmask, &ParseOptions::new())
+    }
 
-        let b1 = chunks[1].parse();
-        if b1.is_err() {
-            return Err(ParseError::GenericError { position: "byte 1".to_string(), value: chunks[1].to_string() })
-        }
+    /// Constructs a subnet mask from string slice with custom parse options
+    /// 
+    /// Parameters:
+    /// * `netmask`: string slice value of subnet mask
+    /// * `options`: parse options
+    pub fn from_str_with_options(netmask: &str, options: &ParseOptions) -> Result<SubnetMask, ParseError> {
+        let [b0, b1, b2, b3] = parse_dotted(netmask, options)?;
+        Ok(SubnetMask::new(b0, b1, b2, b3))
+    }
+
+    /// Constructs a subnet mask from its 32 bit numeric value
+    /// 
+    /// Parameters:
+    /// * `value`: numeric value of subnet mask
+    pub fn from_u32(value: u32) -> SubnetMask {
+        let [b0, b1, b2, b3] = value.to_be_bytes();
+        SubnetMask::new(b0, b1, b2, b3)
+    }
+
+    /// Converts a subnet mask into its 32 bit numeric value
+    pub fn to_u32(&self) -> u32 {
+        u32::from_be_bytes([self.b0, self.b1, self.b2, self.b3])
+    }
+
+    /// Returns the wildcard (inverse) mask of this subnet mask
+    pub fn to_wildcard(&self) -> WildcardMask {
+        WildcardMask::from_u32(!self.to_u32())
+    }
 
-        let b2 = chunks[2].parse();
-        if b2.is_err() {
-            return Err(ParseError::GenericError { position: "byte 2".to_string(), value: chunks[2].to_string() })
+    /// Checks whether all set bits of this subnet mask come before all unset ones
+    pub fn is_contiguous(&self) -> bool {
+        let bits = self.to_u32();
+        bits.leading_ones() + bits.trailing_zeros() == MAX_CIDR as u32
+    }
+
+    /// Converts a Subnet Mask to CIDR value. Fails when the mask is not contiguous
+    /// (e.g. `255.0.255.0`), as such a mask has no CIDR equivalent.
+    pub fn to_cidr(&self) -> Result<u8, NetmaskError> {
+        if !self.is_contiguous() {
+            return Err(NetmaskError::NonContiguous { mask: self.to_string() });
         }
 
-        let b3 = chunks[3].parse();
-        if b3.is_err() {
-            return Err(ParseError::GenericError { position: "byte 3".to_string(), value: chunks[3].to_string() })
+        Ok(self.to_u32().leading_ones() as u8)
+    }
+}
+
+impl FromStr for SubnetMask {
+    type Err = ParseError;
+
+    /// Constructs a subnet mask from string slice
+    fn from_str(value: &str) -> Result<SubnetMask, ParseError> {
+        SubnetMask::from_str_with_options(value, &ParseOptions::new())
+    }
+}
+
+impl fmt::Display for SubnetMask {
+    /// Formats a subnet mask as a human readable dot.decimal string
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}.{}", self.b0, self.b1, self.b2, self.b3)
+    }
+}
+
+impl WildcardMask {
+    /// Constructs a new WildcardMask
+    /// 
+    /// Parameters:
+    /// * `b0`: first byte of wildcard mask
+    /// * `b1`: second byte of wildcard mask
+    /// * `b2`: third byte of wildcard mask
+    /// * `b3`: fourth byte of wildcard mask
+    pub fn new(b0: u8, b1: u8, b2: u8, b3: u8) -> WildcardMask {
+        WildcardMask { b0, b1, b2, b3 }
+    }
+
+    /// Constructs the wildcard mask matching a whole subnet with the given CIDR value
+    /// 
+    /// Parameters:
+    /// * `cidr`: CIDR decimal value
+    pub fn from_cidr(cidr: u8) -> Result<WildcardMask, NetmaskError> {
+        Ok(SubnetMask::from_cidr(cidr)?.to_wildcard())
+    }
+
+    /// Constructs a wildcard mask from string
+    /// 
+    /// Parameters:
+    /// * `wildcard`: String value of wildcard mask
+    pub fn from_string(wildcard: String) -> Result<WildcardMask, ParseError> {
+        let [b0, b1, b2, b3] = parse_dotted(&wildcard, &ParseOptions::new())?;
+        Ok(WildcardMask::new(b0, b1, b2, b3))
+    }
+
+    /// Constructs a wildcard mask from its 32 bit numeric value
+    /// 
+    /// Parameters:
+    /// * `value`: numeric value of wildcard mask
+    pub fn from_u32(value: u32) -> WildcardMask {
+        let [b0, b1, b2, b3] = value.to_be_bytes();
+        WildcardMask::new(b0, b1, b2, b3)
+    }
+
+    /// Converts a wildcard mask into its 32 bit numeric value
+    pub fn to_u32(&self) -> u32 {
+        u32::from_be_bytes([self.b0, self.b1, self.b2, self.b3])
+    }
+
+    /// Converts a wildcard mask into the equivalent subnet mask. Fails when the
+    /// wildcard does not describe a whole subnet (e.g. `0.255.0.255`).
+    pub fn to_netmask(&self) -> Result<SubnetMask, NetmaskError> {
+        let netmask = SubnetMask::from_u32(!self.to_u32());
+        if !netmask.is_contiguous() {
+            return Err(NetmaskError::NonContiguous { mask: self.to_string() });
         }
 
-        Ok(SubnetMask::new(b0.unwrap(), b1.unwrap(), b2.unwrap(), b3.unwrap()))
+        Ok(netmask)
     }
 
-    /// Constructs a subnet mask from string
+    /// Converts a wildcard mask to CIDR value. Fails when the wildcard does not
+    /// describe a whole subnet.
+    pub fn to_cidr(&self) -> Result<u8, NetmaskError> {
+        self.to_netmask()?.to_cidr()
+    }
+}
+
+impl FromStr for WildcardMask {
+    type Err = ParseError;
+
+    /// Constructs a wildcard mask from string slice
+    fn from_str(value: &str) -> Result<WildcardMask, ParseError> {
+        WildcardMask::from_string(value.to_string())
+    }
+}
+
+impl fmt::Display for WildcardMask {
+    /// Formats a wildcard mask as a human readable dot.decimal string
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}.{}", self.b0, self.b1, self.b2, self.b3)
+    }
+}
+
+impl IPv6Address {
+    /// Creates a new IPv6 address struct
     /// 
     /// Parameters:
-    /// * `netmask`: string slice value of subnet mask
-    pub fn from_str(netmask: &str) -> Result<SubnetMask, ParseError> {
-        SubnetMask::from_string(netmask.to_string())
+    /// * `segments`: 16 bit segments of IPv6 address
+    /// 
+    /// Addresses used to carry a CIDR value as second parameter. Build an
+    /// [`IPv6Network`](crate::network::IPv6Network) instead.
+    pub fn new(segments: [u16; V6_SEGMENTS]) -> IPv6Address {
+        IPv6Address { segments }
+    }
+
+    /// Creates a new IPv6 address struct
+    /// 
+    /// Parameters:
+    /// * `segments`: 16 bit segments of IPv6 address
+    #[deprecated(note = "addresses never carry a CIDR value anymore, use `IPv6Address::new`")]
+    pub fn new_without_cidr(segments: [u16; V6_SEGMENTS]) -> IPv6Address {
+        IPv6Address::new(segments)
+    }
+
+    /// Construct an IPv6 address from string parameter
+    /// 
+    /// Accepts `::` compression and an embedded dotted IPv4 tail (e.g. `::ffff:10.0.0.1`).
+    /// A CIDR suffix is rejected, parse an [`IPv6Network`](crate::network::IPv6Network) instead.
+    /// 
+    /// Parameters:
+    /// * `ip_address`: String value with IPv6 address
+    pub fn from_string(ip_address: String) -> Result<IPv6Address, ParseError> {
+        reject_prefix(&ip_address)?;
+        Ok(IPv6Address::new(parse_v6_segments(&ip_address)?))
+    }
+}
+
+impl FromStr for IPv6Address {
+    type Err = ParseError;
+
+    /// Constructs an IPv6 address from string slice
+    fn from_str(ip_address: &str) -> Result<IPv6Address, ParseError> {
+        IPv6Address::from_string(ip_address.to_string())
+    }
+}
+
+impl fmt::Display for IPv6Address {
+    /// Formats an IPv6 address as its canonical RFC 5952 string
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format_v6_segments(&self.segments))
+    }
+}
+
+impl IPv6SubnetMask {
+    /// Constructs a new IPv6SubnetMask
+    /// 
+    /// Parameters:
+    /// * `segments`: 16 bit segments of netmask
+    pub fn new(segments: [u16; V6_SEGMENTS]) -> IPv6SubnetMask {
+        IPv6SubnetMask { segments }
+    }
+
+    /// Constructs a new IPv6SubnetMask given a CIDR value
+    /// 
+    /// Parameters:
+    /// * `cidr`: CIDR decimal value
+    pub fn from_cidr(cidr: u8) -> Result<IPv6SubnetMask, NetmaskError> {
+        Ok(IPv6SubnetMask::from_prefix(IPv6PrefixLen::new(cidr)?))
+    }
+
+    /// Constructs a new IPv6SubnetMask given a prefix length, which is always valid
+    /// 
+    /// Parameters:
+    /// * `prefix`: prefix length
+    pub fn from_prefix(prefix: IPv6PrefixLen) -> IPv6SubnetMask {
+        // A zero CIDR would overflow the shift, it simply means no bits set
+        let cidr = prefix.get();
+        let bits = if cidr == 0 { 0 } else { u128::MAX << (MAX_CIDR_V6 - cidr) };
+
+        IPv6SubnetMask::new(u128_to_segments(bits))
+    }
+
+    /// Constructs an IPv6 subnet mask from string
+    /// 
+    /// Parameters:
+    /// * `netmask`: String value of subnet mask
+    pub fn from_string(netmask: String) -> Result<IPv6SubnetMask, ParseError> {
+        Ok(IPv6SubnetMask::new(parse_v6_segments(&netmask)?))
+    }
+
+    /// Checks whether all set bits of this subnet mask come before all unset ones
+    pub fn is_contiguous(&self) -> bool {
+        let bits = self.segments.iter().fold(0_u128, |bits, &s| (bits << 16) | s as u128);
+        bits.leading_ones() + bits.trailing_zeros() == MAX_CIDR_V6 as u32
+    }
+
+    /// Converts an IPv6 subnet mask to CIDR value. Fails when the mask is not contiguous.
+    pub fn to_cidr(&self) -> Result<u8, NetmaskError> {
+        if !self.is_contiguous() {
+            return Err(NetmaskError::NonContiguous { mask: self.to_string() });
+        }
+
+        Ok(self.segments.iter().map(|s| s.count_ones()).sum::<u32>() as u8)
+    }
+}
+
+impl FromStr for IPv6SubnetMask {
+    type Err = ParseError;
+
+    /// Constructs an IPv6 subnet mask from string slice
+    fn from_str(netmask: &str) -> Result<IPv6SubnetMask, ParseError> {
+        IPv6SubnetMask::from_string(netmask.to_string())
+    }
+}
+
+impl fmt::Display for IPv6SubnetMask {
+    /// Formats an IPv6 subnet mask as its canonical RFC 5952 string
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format_v6_segments(&self.segments))
+    }
+}
+
+/// Splits a 128 bit value into IPv6 segments, most significant first
+fn u128_to_segments(value: u128) -> [u16; V6_SEGMENTS] {
+    let mut segments = [0; V6_SEGMENTS];
+    for (i, segment) in segments.iter_mut().enumerate() {
+        *segment = (value >> (16 * (V6_SEGMENTS - 1 - i))) as u16;
+    }
+
+    segments
+}
+
+/// Parses an IPv6 address string into segments
+fn parse_v6_segments(address: &str) -> Result<[u16; V6_SEGMENTS], ParseError> {
+    let mut segments = [0; V6_SEGMENTS];
+
+    match address.find("::") {
+        // No compression: all eight segments have to be there
+        None => {
+            let groups = parse_v6_groups(address, 0, true, V6_SEGMENTS)?;
+            if groups.len() < V6_SEGMENTS {
+                return Err(ParseError::MissingComponent { position: format!("IPv6 segment {}", groups.len()), offset: address.len() });
+            }
+
+            segments.copy_from_slice(&groups);
+        }
+        // Compression: "::" stands for at least one zero segment
+        Some(i) => {
+            let (head, tail) = (&address[..i], &address[i + 2..]);
+            if let Some(j) = tail.find("::") {
+                return Err(ParseError::TrailingGarbage { value: tail[j..].to_string(), offset: i + 2 + j });
+            }
+
+            // "::" takes at least one segment, the tail gets what is left
+            let head = parse_v6_groups(head, 0, false, V6_SEGMENTS - 1)?;
+            let tail = parse_v6_groups(tail, i + 2, true, V6_SEGMENTS - 1 - head.len())?;
+            segments[..head.len()].copy_from_slice(&head);
+            segments[V6_SEGMENTS - tail.len()..].copy_from_slice(&tail);
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Parses a colon separated run of hex groups starting at byte `offset` of the
+/// input. When `allow_v4` is set the last group may be a dotted IPv4 address,
+/// which yields two segments. Groups past `limit` segments are reported as
+/// trailing garbage, starting at their leading colon.
+fn parse_v6_groups(groups: &str, offset: usize, allow_v4: bool, limit: usize) -> Result<Vec<u16>, ParseError> {
+    let mut result = Vec::with_capacity(V6_SEGMENTS);
+    if groups.is_empty() {
+        return Ok(result);
+    }
+
+    let mut position = offset;
+    let chunks: Vec<&str> = groups.split(':').collect();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let is_v4 = allow_v4 && i == chunks.len() - 1 && chunk.contains('.');
+        let size = if is_v4 { 2 } else { 1 };
+        if result.len() + size > limit {
+            let start = if i == 0 { position } else { position - 1 };
+            return Err(ParseError::TrailingGarbage { value: groups[start - offset..].to_string(), offset: start });
+        }
+
+        if is_v4 {
+            let bytes = parse_dotted(chunk, &ParseOptions::new()).map_err(|e| e.offset_by(position))?;
+            result.push(u16::from_be_bytes([bytes[0], bytes[1]]));
+            result.push(u16::from_be_bytes([bytes[2], bytes[3]]));
+            continue;
+        }
+
+        let name = format!("IPv6 group {}", i);
+        if chunk.is_empty() {
+            return Err(ParseError::EmptyComponent { position: name, offset: position });
+        }
+
+        if chunk.len() > 4 || !chunk.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ParseError::GenericError { position: name, value: chunk.to_string(), offset: position });
+        }
+
+        // Only hex digits left, this cannot fail
+        result.push(u16::from_str_radix(chunk, 16).unwrap_or_default());
+        position += chunk.len() + 1;
+    }
+
+    Ok(result)
+}
+
+/// Formats IPv6 segments following RFC 5952: lowercase hex, longest run of
+/// zero segments compressed to `::`, IPv4-mapped addresses in dotted form
+fn format_v6_segments(segments: &[u16; V6_SEGMENTS]) -> String {
+    let join = |s: &[u16]| s.iter().map(|v| format!("{:x}", v)).collect::<Vec<String>>().join(":");
+
+    if segments[..5] == [0; 5] && segments[5] == 0xFFFF {
+        return format!("::ffff:{}.{}.{}.{}", segments[6] >> 8, segments[6] & 0xFF, segments[7] >> 8, segments[7] & 0xFF);
     }
 
-    /// Converts a Subnet Mask to CIDR value
-    pub fn to_cidr(&self) -> u8 {
-        (self.b0.count_ones() + self.b1.count_ones() + self.b2.count_ones() + self.b3.count_ones()) as u8
+    // Find the longest run of zero segments, first one wins on ties
+    let (mut best_start, mut best_len) = (0, 0);
+    let mut i = 0;
+    while i < V6_SEGMENTS {
+        if segments[i] != 0 {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < V6_SEGMENTS && segments[i] == 0 {
+            i += 1;
+        }
+
+        if i - start > best_len {
+            best_start = start;
+            best_len = i - start;
+        }
     }
 
-    /// Returns a human readable dot.decimal string of this Subnet mask
-    pub fn to_string(&self) -> String {
-        format!("{}.{}.{}.{}", self.b0, self.b1, self.b2, self.b3)
+    // A single zero segment is never compressed
+    if best_len < 2 {
+        return join(segments);
     }
+
+    format!("{}::{}", join(&segments[..best_start]), join(&segments[best_start + best_len..]))
 }
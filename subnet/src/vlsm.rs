@@ -0,0 +1,145 @@
+use std::cmp::Reverse;
+use std::str::FromStr;
+use custom_error::custom_error;
+use crate::constants::MAX_CIDR;
+use crate::network::{IPNetwork, PrefixLen};
+use crate::parse::{parse_decimal, ParseOptions};
+use crate::types::{IPAddress, ParseError, SubnetMask};
+
+custom_error!{
+    /// Describes an error while planning a VLSM allocation
+    pub VlsmError
+        DoesNotFit{name: String, hosts: u32} = "Requirement '{name}' ({hosts} hosts) does not fit in the parent network"
+}
+
+/// A named number of hosts that needs its own subnet
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostRequirement {
+    /// Name of the requirement, e.g. `sales`
+    pub name: String,
+    /// Number of usable host addresses needed
+    pub hosts: u32,
+}
+
+/// A subnet allocated to a requirement by the VLSM planner
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Allocation {
+    /// Name of the requirement
+    pub name: String,
+    /// Number of usable host addresses requested
+    pub hosts: u32,
+    /// Network of the allocated subnet
+    pub network: IPNetwork,
+    /// Subnet mask of the allocated subnet
+    pub netmask: SubnetMask,
+    /// Broadcast address of the allocated subnet, `None` for /31 and /32
+    pub broadcast: Option<IPAddress>,
+    /// Usable host addresses left over after the requested ones
+    pub spare_hosts: u64,
+}
+
+impl HostRequirement {
+    /// Creates a new host requirement
+    /// 
+    /// Parameters:
+    /// * `name`: name of the requirement
+    /// * `hosts`: number of usable host addresses needed
+    pub fn new(name: &str, hosts: u32) -> HostRequirement {
+        HostRequirement { name: name.to_string(), hosts }
+    }
+
+    /// Calculates the smallest subnet (largest CIDR value) with enough usable
+    /// host addresses, following the same /31 and /32 rules as the subnet summary
+    pub fn min_cidr(&self) -> Option<u8> {
+        (0..=MAX_CIDR).rev().find(|&cidr| usable_hosts(cidr) >= self.hosts as u64)
+    }
+}
+
+impl FromStr for HostRequirement {
+    type Err = ParseError;
+
+    /// Constructs a host requirement from a string slice like `sales: 120 hosts`
+    /// (the `host` or `hosts` suffix is optional). At least one host is required.
+    fn from_str(requirement: &str) -> Result<HostRequirement, ParseError> {
+        let separator = match requirement.find(':') {
+            Some(i) => i,
+            None => return Err(ParseError::MissingComponent { position: "requirement separator".to_string(), offset: requirement.len() }),
+        };
+
+        let name = requirement[..separator].trim();
+        if name.is_empty() {
+            return Err(ParseError::EmptyComponent { position: "requirement name".to_string(), offset: 0 });
+        }
+
+        let hosts = &requirement[separator + 1..];
+        let offset = separator + 1 + hosts.len() - hosts.trim_start().len();
+        let hosts = hosts.trim();
+        let hosts = hosts.strip_suffix("hosts").or_else(|| hosts.strip_suffix("host")).unwrap_or(hosts).trim_end();
+
+        let position = format!("hosts of '{}'", name);
+        let count = parse_decimal(hosts, offset, &position, u32::MAX, &ParseOptions::new())?;
+        if count == 0 {
+            return Err(ParseError::OutOfRange { position, value: hosts.to_string(), offset });
+        }
+
+        Ok(HostRequirement::new(name, count))
+    }
+}
+
+impl IPNetwork {
+    /// Allocates a subnet for every requirement inside the subnet of this
+    /// network. Requirements are served largest first, each one getting the
+    /// smallest subnet that holds its hosts; the table is returned in that order.
+    /// 
+    /// Parameters:
+    /// * `requirements`: named host requirements to allocate
+    pub fn vlsm(&self, requirements: &[HostRequirement]) -> Result<Vec<Allocation>, VlsmError> {
+        let parent = self.summary();
+        let end = parent.network.address.to_u32() as u64 + parent.total_hosts;
+
+        let mut sorted = requirements.to_vec();
+        sorted.sort_by_key(|r| Reverse(r.hosts));
+
+        let mut cursor = parent.network.address.to_u32() as u64;
+        let mut result = Vec::with_capacity(sorted.len());
+
+        for requirement in sorted {
+            let does_not_fit = || VlsmError::DoesNotFit { name: requirement.name.clone(), hosts: requirement.hosts };
+
+            let cidr = requirement.min_cidr().ok_or_else(does_not_fit)?;
+            if cidr < self.cidr() {
+                return Err(does_not_fit());
+            }
+
+            // Align cursor on the block size of the new subnet
+            let size = 1_u64 << (MAX_CIDR - cidr);
+            let start = cursor.div_ceil(size) * size;
+            if start + size > end {
+                return Err(does_not_fit());
+            }
+
+            let summary = IPNetwork::from_u32(start as u32, PrefixLen::clamped(cidr)).summary();
+            result.push(Allocation {
+                name: requirement.name.clone(),
+                hosts: requirement.hosts,
+                network: summary.network,
+                netmask: summary.netmask,
+                broadcast: summary.broadcast,
+                spare_hosts: summary.usable_hosts - requirement.hosts as u64,
+            });
+
+            cursor = start + size;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Number of usable host addresses in a subnet with the given CIDR value
+fn usable_hosts(cidr: u8) -> u64 {
+    match cidr {
+        32 => 1,
+        31 => 2,
+        _ => (1_u64 << (MAX_CIDR - cidr)) - 2,
+    }
+}